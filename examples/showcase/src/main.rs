@@ -1,5 +1,5 @@
 use holofoil::card;
-use holofoil::{Bytes, Card, Configuration, Light, Pipeline, Quaternion, Vector};
+use holofoil::{Bytes, Card, Configuration, DebugView, Light, Pipeline, Quaternion, Vector};
 
 use iced::mouse;
 use iced::theme;
@@ -90,6 +90,7 @@ enum Message {
     Spin(Vector2),
     SamplesChanged(u32),
     MaxIterationsChanged(u32),
+    DebugViewChanged(Option<DebugView>),
     LightChanged(Light),
     LinkClicked(Link),
 }
@@ -181,6 +182,9 @@ impl Showcase {
             Message::MaxIterationsChanged(max_iterations) => {
                 self.viewer.configuration.max_iterations = max_iterations;
             }
+            Message::DebugViewChanged(debug_view) => {
+                self.viewer.configuration.debug_view = debug_view;
+            }
             Message::LightChanged(light) => {
                 self.viewer.configuration.light = light;
             }
@@ -286,6 +290,7 @@ impl Showcase {
         let Configuration {
             n_samples,
             max_iterations,
+            debug_view,
             ..
         } = self.viewer.configuration;
 
@@ -306,6 +311,20 @@ impl Showcase {
                     Message::MaxIterationsChanged,
                     u32::to_string,
                 ),
+                pick_list(
+                    Some(debug_view),
+                    [None]
+                        .into_iter()
+                        .chain(DebugView::ALL.map(Some))
+                        .collect::<Vec<_>>(),
+                    |debug_view| match debug_view {
+                        Some(debug_view) => format!("Debug {debug_view}"),
+                        None => "No debugging".to_owned(),
+                    },
+                )
+                .on_select(Message::DebugViewChanged)
+                .width(Fill)
+                .text_size(14),
             ]
             .spacing(5),
         )
@@ -565,19 +584,12 @@ struct Holofoil {
 
 struct Renderer {
     pipeline: Pipeline,
-    #[cfg(not(target_arch = "wasm32"))]
-    watcher: Watcher,
 }
 
 impl shader::Pipeline for Renderer {
     fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
-        #[cfg(not(target_arch = "wasm32"))]
-        let watcher = Watcher::new(device, queue, format);
-
         Self {
             pipeline: pipeline(device, queue, format),
-            #[cfg(not(target_arch = "wasm32"))]
-            watcher,
         }
     }
 }
@@ -596,21 +608,33 @@ impl shader::Primitive for Holofoil {
         let mut cache = self.cache.lock().unwrap();
 
         #[cfg(not(target_arch = "wasm32"))]
-        if let Some(pipeline) = renderer.watcher.latest() {
-            renderer.pipeline = pipeline;
-            cache.card = None;
+        match renderer.pipeline.reload(device) {
+            Ok(true) => log::info!("Shader reloaded"),
+            Ok(false) => {}
+            Err(error) => log::error!("Failed to reload shader: {error}"),
         }
 
-        let mut card = cache
-            .card
-            .take()
-            .unwrap_or_else(|| renderer.pipeline.upload(device, queue, &self.card));
+        let mut card = match cache.card.take() {
+            Some(card) => card,
+            None => match renderer.pipeline.upload(device, queue, &self.card) {
+                Ok(card) => card,
+                Err(error) => {
+                    log::error!("Failed to upload card: {error}");
+                    return;
+                }
+            },
+        };
 
         let Some(bounds) = (*bounds * viewport.scale_factor()).snap() else {
             return;
         };
 
-        renderer.pipeline.configure(queue, self.configuration);
+        if let Err(error) = renderer
+            .pipeline
+            .configure(device, queue, self.configuration)
+        {
+            log::error!("Failed to configure pipeline: {error}");
+        }
 
         card.prepare(
             queue,
@@ -653,37 +677,46 @@ impl Cache {
 
 fn umbreon() -> card::Structure {
     card::Structure {
-        base: load_image(include_bytes!("../assets/sv8-5_en_161_std.png")),
         foil: Some(load_mask(include_bytes!(
             "../assets/sv8-5_en_161_std.foil.png"
         ))),
         etching: Some(load_mask(include_bytes!(
             "../assets/sv8-5_en_161_std.etch.png"
         ))),
-        width: 733,
+        ..card::Structure::new(load_image(include_bytes!("../assets/sv8-5_en_161_std.png")))
     }
 }
 
 fn bellibolt() -> card::Structure {
     card::Structure {
-        base: load_image(include_bytes!("../assets/sv9_en_188_std.png")),
         foil: Some(load_mask(include_bytes!(
             "../assets/sv9_en_188_std.foil.png"
         ))),
         etching: Some(load_mask(include_bytes!(
             "../assets/sv9_en_188_std.etch.png"
         ))),
-        width: 733,
+        ..card::Structure::new(load_image(include_bytes!("../assets/sv9_en_188_std.png")))
     }
 }
 
 fn pipeline(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Pipeline {
-    Pipeline::new(
+    let pipeline = Pipeline::new(
         device,
         queue,
         format,
         load_image(include_bytes!("../assets/pokemon_tcg_back.png")),
     )
+    .expect("Create holofoil pipeline");
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let mut pipeline = pipeline;
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Err(error) = pipeline.watch(concat!(env!("CARGO_MANIFEST_DIR"), "/../../src")) {
+        log::warn!("{error}");
+    }
+
+    pipeline
 }
 
 fn load_image(bytes: &[u8]) -> card::Image {
@@ -696,11 +729,13 @@ fn load_image(bytes: &[u8]) -> card::Image {
     let mut rgba = vec![0; reader.output_buffer_size().unwrap()];
 
     let metadata = reader.next_frame(&mut rgba).unwrap();
-    let bytes = &rgba[..metadata.buffer_size()];
+    let (rgba, width) = crop(&rgba[..metadata.buffer_size()], metadata.width, 4);
 
     card::Image {
-        rgba: Bytes::copy_from_slice(bytes),
-        size: metadata.width,
+        rgba: Bytes::from(rgba),
+        width,
+        height: metadata.height,
+        compression: None,
     }
 }
 
@@ -712,97 +747,29 @@ fn load_mask(bytes: &[u8]) -> card::Mask {
     let mut rgba = vec![0; reader.output_buffer_size().unwrap()];
 
     let metadata = reader.next_frame(&mut rgba).unwrap();
-    let bytes = &rgba[..metadata.buffer_size()];
+    let (pixels, width) = crop(&rgba[..metadata.buffer_size()], metadata.width, 1);
 
     card::Mask {
-        pixels: Bytes::copy_from_slice(bytes),
-        size: metadata.width,
+        pixels: Bytes::from(pixels),
+        width,
+        height: metadata.height,
+        compression: None,
     }
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-struct Watcher {
-    _raw: notify_debouncer_full::Debouncer<
-        notify_debouncer_full::notify::RecommendedWatcher,
-        notify_debouncer_full::RecommendedCache,
-    >,
-    pipelines: Mutex<std::sync::mpsc::Receiver<Pipeline>>,
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-impl Watcher {
-    fn new(device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) -> Self {
-        use notify_debouncer_full::{DebounceEventResult, new_debouncer, notify};
-        use std::path::PathBuf;
-        use std::sync::mpsc;
-        use std::thread;
-        use std::time::Duration;
-
-        let (sender, receiver) = mpsc::channel();
-        let device = device.clone();
-        let queue = queue.clone();
-
-        let mut debouncer = new_debouncer(
-            Duration::from_millis(10),
-            None,
-            move |events: DebounceEventResult| {
-                let Ok(events) = events else {
-                    return;
-                };
-
-                let modified = events.iter().any(|event| {
-                    event.paths.iter().any(|path| path.ends_with("shader.wgsl"))
-                        && (event.kind.is_modify()
-                            || event.kind.is_remove()
-                            || event.kind.is_create())
-                });
-
-                if modified {
-                    let device = device.clone();
-                    let queue = queue.clone();
-
-                    log::info!("Recompiling shader...");
+// The scans in the assets are padded into squares
+fn crop(pixels: &[u8], width: u32, channels: usize) -> (Vec<u8>, u32) {
+    let cropped = width * 733 / 1024;
+    let offset = (width - cropped) as usize / 2 * channels;
+    let row = width as usize * channels;
 
-                    if let Ok(pipeline) =
-                        thread::spawn(move || pipeline(&device, &queue, format)).join()
-                    {
-                        let _ = sender.send(pipeline);
-                    }
-                }
-            },
-        )
-        .unwrap();
-
-        debouncer
-            .watch(
-                &PathBuf::from(format!("{}/../../src", env!("CARGO_MANIFEST_DIR"))),
-                notify::RecursiveMode::NonRecursive,
-            )
-            .unwrap();
+    let pixels = pixels
+        .chunks_exact(row)
+        .flat_map(|row| &row[offset..offset + cropped as usize * channels])
+        .copied()
+        .collect();
 
-        Self {
-            _raw: debouncer,
-            pipelines: Mutex::new(receiver),
-        }
-    }
-
-    fn latest(&mut self) -> Option<Pipeline> {
-        self.pipelines.lock().unwrap().try_iter().last()
-    }
-}
-
-#[cfg(not(target_arch = "wasm32"))]
-impl Drop for Watcher {
-    fn drop(&mut self) {
-        if let Ok(watcher) =
-            notify_debouncer_full::new_debouncer(std::time::Duration::from_millis(10), None, |_| {})
-        {
-            self._raw = watcher;
-        }
-
-        // This avoids a SIGSEGV because the notifier captures `Device`
-        std::thread::sleep(std::time::Duration::from_millis(100));
-    }
+    (pixels, cropped)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
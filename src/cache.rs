@@ -0,0 +1,210 @@
+use crate::card::{self, Card};
+use crate::{Error, Pipeline};
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Arc;
+
+/// A pool of cards sharing a budget of GPU memory.
+///
+/// The cache retains the [`card::Structure`] of every card, uploads cards
+/// on demand, and evicts the least recently used ones when their textures
+/// exceed the budget. Evicted cards are uploaded again the next time they
+/// are prepared.
+///
+/// Call [`CardCache::begin_frame`] before preparing the cards of a frame.
+/// Cards prepared in the current frame are never evicted, so the memory may
+/// exceed the budget while they do not fit.
+#[derive(Debug)]
+pub struct CardCache<K> {
+    entries: HashMap<K, Entry>,
+    budget: u64,
+    memory: u64,
+    clock: u64,
+    frame: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    structure: Arc<card::Structure>,
+    card: Option<Card>,
+    last_used: u64,
+}
+
+impl<K: Eq + Hash + Clone> CardCache<K> {
+    /// Creates an empty [`CardCache`] with the given budget, in bytes.
+    pub fn new(budget: u64) -> Self {
+        Self {
+            entries: HashMap::new(),
+            budget,
+            memory: 0,
+            clock: 0,
+            frame: 0,
+        }
+    }
+
+    /// Starts a new frame.
+    ///
+    /// Cards prepared in earlier frames may be evicted from now on, least
+    /// recently used first, to fit the cards of the new frame.
+    pub fn begin_frame(&mut self) {
+        self.clock += 1;
+        self.frame = self.clock;
+    }
+
+    /// Adds a card to the cache, replacing any card with the same key.
+    ///
+    /// Nothing is uploaded until the card is prepared.
+    pub fn insert(&mut self, key: K, structure: Arc<card::Structure>) {
+        self.remove(&key);
+
+        let _ = self.entries.insert(
+            key,
+            Entry {
+                structure,
+                card: None,
+                last_used: 0,
+            },
+        );
+    }
+
+    /// Removes a card from the cache, freeing its textures.
+    pub fn remove(&mut self, key: &K) -> Option<Arc<card::Structure>> {
+        let entry = self.entries.remove(key)?;
+
+        if let Some(card) = &entry.card {
+            self.memory -= card.memory();
+        }
+
+        Some(entry.structure)
+    }
+
+    /// Uploads the card with the given key if needed, and prepares it for
+    /// rendering.
+    ///
+    /// Returns `false` if there is no card with the given key.
+    ///
+    /// Fails if the card needs to be uploaded and its structure is invalid.
+    pub fn prepare(
+        &mut self,
+        pipeline: &Pipeline,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        key: &K,
+        parameters: card::Parameters,
+    ) -> Result<bool, Error> {
+        let Some(card) = self.load(pipeline, device, queue, key)? else {
+            return Ok(false);
+        };
+
+        card.prepare(queue, parameters);
+
+        Ok(true)
+    }
+
+    /// Uploads the card with the given key if needed, and prepares it for
+    /// rendering through a [`StagingBelt`], like [`CardCache::prepare`].
+    ///
+    /// [`StagingBelt`]: wgpu::util::StagingBelt
+    #[allow(clippy::too_many_arguments)]
+    pub fn prepare_with(
+        &mut self,
+        pipeline: &Pipeline,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut wgpu::util::StagingBelt,
+        key: &K,
+        parameters: card::Parameters,
+    ) -> Result<bool, Error> {
+        let Some(card) = self.load(pipeline, device, queue, key)? else {
+            return Ok(false);
+        };
+
+        card.prepare_with(encoder, belt, parameters);
+
+        Ok(true)
+    }
+
+    /// Renders the card with the given key, if it is uploaded.
+    pub fn render(&self, pipeline: &Pipeline, render_pass: &mut wgpu::RenderPass<'_>, key: &K) {
+        if let Some(card) = self.get(key) {
+            pipeline.render(render_pass, card);
+        }
+    }
+
+    /// Returns the uploaded card with the given key, if any.
+    pub fn get(&self, key: &K) -> Option<&Card> {
+        self.entries.get(key)?.card.as_ref()
+    }
+
+    /// Returns the structure of the card with the given key, if any.
+    pub fn structure(&self, key: &K) -> Option<&Arc<card::Structure>> {
+        self.entries.get(key).map(|entry| &entry.structure)
+    }
+
+    /// The GPU memory taken by the uploaded cards, in bytes.
+    pub fn memory(&self) -> u64 {
+        self.memory
+    }
+
+    pub fn budget(&self) -> u64 {
+        self.budget
+    }
+
+    /// Changes the budget, evicting cards right away if needed.
+    pub fn set_budget(&mut self, budget: u64) {
+        self.budget = budget;
+        self.trim();
+    }
+
+    // Uploads the card with the given key if needed, and marks it as used in
+    // the current frame
+    fn load(
+        &mut self,
+        pipeline: &Pipeline,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        key: &K,
+    ) -> Result<Option<&mut Card>, Error> {
+        self.clock += 1;
+
+        let Some(entry) = self.entries.get_mut(key) else {
+            return Ok(None);
+        };
+
+        entry.last_used = self.clock;
+
+        if entry.card.is_none() {
+            let card = pipeline.upload(device, queue, &entry.structure)?;
+            self.memory += card.memory();
+
+            entry.card = Some(card);
+        }
+
+        self.trim();
+
+        Ok(self
+            .entries
+            .get_mut(key)
+            .and_then(|entry| entry.card.as_mut()))
+    }
+
+    // The cards of the current frame are never evicted
+    fn trim(&mut self) {
+        while self.memory > self.budget {
+            let Some(entry) = self
+                .entries
+                .values_mut()
+                .filter(|entry| entry.card.is_some() && entry.last_used < self.frame)
+                .min_by_key(|entry| entry.last_used)
+            else {
+                break;
+            };
+
+            if let Some(card) = entry.card.take() {
+                self.memory -= card.memory();
+            }
+        }
+    }
+}
@@ -1,34 +1,463 @@
-use crate::{Bytes, Quaternion};
+use crate::mipmap;
+use crate::raymarch::{self, Geometry, Surface};
+use crate::{Bytes, Error, Quaternion, Vector};
 
-#[derive(Debug, Clone)]
+pub use crate::compression::Compression;
+
+use std::borrow::Cow;
+use std::fmt;
+use std::mem;
+
+/// A card uploaded to the GPU.
+///
+/// It is not [`Clone`], since it keeps track of what it last wrote to its
+/// buffers.
+#[derive(Debug)]
 pub struct Card {
     pub(crate) width: u32,
     pub(crate) height: u32,
+    pub(crate) shape: Shape,
+    pub(crate) shell: Option<Sleeve>,
+    pub(crate) label: f32,
     pub(crate) instance: wgpu::Buffer,
-    pub(crate) _base: wgpu::Texture,
-    pub(crate) _foil: Option<wgpu::Texture>,
-    pub(crate) _etching: Option<wgpu::Texture>,
+    pub(crate) uploaded: Option<Instance>,
+    pub(crate) base: wgpu::Texture,
+    pub(crate) foil: Option<wgpu::Texture>,
+    pub(crate) etching: Option<wgpu::Texture>,
+    pub(crate) base_pixels: Option<Pixels>,
+    pub(crate) foil_pixels: Option<Pixels>,
+    pub(crate) etching_pixels: Option<Pixels>,
+    pub(crate) _silhouette: Option<wgpu::Texture>,
+    pub(crate) silhouette: Option<Mask>,
+    pub(crate) silhouette_alpha: bool,
+    pub(crate) _label: Option<wgpu::Texture>,
+    pub(crate) _wear: Option<wgpu::Texture>,
+    pub(crate) _relief: Option<wgpu::Texture>,
+    pub(crate) _back: Option<Textures>,
     pub(crate) binding: wgpu::BindGroup,
+    pub(crate) flags: u32,
+    pub(crate) downscaled: Vec<(Layer, f32)>,
 }
 
 impl Card {
+    /// Writes the [`Parameters`] of the card for the next render.
+    ///
+    /// Nothing is written if they have not changed since the last time.
     pub fn prepare(&mut self, queue: &wgpu::Queue, parameters: Parameters) {
+        if let Some(instance) = self.update(parameters) {
+            queue.write_buffer(&self.instance, 0, bytemuck::bytes_of(&instance));
+        }
+    }
+
+    /// Writes the [`Parameters`] of the card for the next render through a
+    /// [`StagingBelt`], like [`Card::prepare`].
+    ///
+    /// This batches the writes of many cards into a single encoder. The belt
+    /// must be finished before submitting it.
+    ///
+    /// [`StagingBelt`]: wgpu::util::StagingBelt
+    pub fn prepare_with(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        belt: &mut wgpu::util::StagingBelt,
+        parameters: Parameters,
+    ) {
+        if let Some(instance) = self.update(parameters) {
+            let size = wgpu::BufferSize::new(mem::size_of::<Instance>() as u64)
+                .expect("Instance is not empty");
+
+            belt.write_buffer(encoder, &self.instance, 0, size)
+                .copy_from_slice(bytemuck::bytes_of(&instance));
+        }
+    }
+
+    // Returns the new instance data, if it changed since the last upload
+    fn update(&mut self, parameters: Parameters) -> Option<Instance> {
+        let Parameters { viewport, rotation } = parameters;
+        let (shape, shape_parameters) = self.shape.encode();
+        let shell = self.shell.unwrap_or(Sleeve::NONE);
+
+        let instance = Instance {
+            viewport: [
+                viewport.x as f32,
+                viewport.y as f32,
+                viewport.width as f32,
+                viewport.height as f32,
+            ],
+            size: [self.width as f32, self.height as f32],
+            rotation: [rotation.a.x, rotation.a.y, rotation.a.z, rotation.w],
+            flags: self.flags,
+            shape,
+            shape_parameters,
+            shell: [shell.margin, shell.thickness, shell.refraction, shell.gloss],
+            shell_tint: shell.tint,
+            label: self.label,
+        };
+
+        if self.uploaded == Some(instance) {
+            return None;
+        }
+
+        self.uploaded = Some(instance);
+
+        Some(instance)
+    }
+
+    /// Replaces the pixels of the base image inside the given [`Region`].
+    ///
+    /// The `rgba` pixels must be tightly packed rows of the region. If the
+    /// card has a [`Silhouette::Alpha`], its silhouette is computed again from
+    /// the whole image.
+    ///
+    /// Fails if the region does not fit in the image, the pixels do not match
+    /// the region, or the image was uploaded compressed or downscaled.
+    pub fn update_base(
+        &mut self,
+        queue: &wgpu::Queue,
+        region: Region,
+        rgba: &[u8],
+    ) -> Result<(), Error> {
+        self.update_layer(queue, Layer::Base, region, rgba)
+    }
+
+    /// Replaces the pixels of the foil mask inside the given [`Region`].
+    ///
+    /// Fails if the card was uploaded without a foil mask, or like
+    /// [`Card::update_base`].
+    pub fn update_foil(
+        &mut self,
+        queue: &wgpu::Queue,
+        region: Region,
+        pixels: &[u8],
+    ) -> Result<(), Error> {
+        self.update_layer(queue, Layer::Foil, region, pixels)
+    }
+
+    /// Replaces the pixels of the etching mask inside the given [`Region`].
+    ///
+    /// Fails if the card was uploaded without an etching mask, or like
+    /// [`Card::update_base`].
+    pub fn update_etching(
+        &mut self,
+        queue: &wgpu::Queue,
+        region: Region,
+        pixels: &[u8],
+    ) -> Result<(), Error> {
+        self.update_layer(queue, Layer::Etching, region, pixels)
+    }
+
+    fn update_layer(
+        &mut self,
+        queue: &wgpu::Queue,
+        layer: Layer,
+        region: Region,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        // Regions are in the coordinates of the original pixels
+        if self
+            .downscaled
+            .iter()
+            .any(|(downscaled, _)| *downscaled == layer)
+        {
+            return Err(Error::Downscaled { layer });
+        }
+
+        let (texture, pixels) = match layer {
+            Layer::Base => (Some(&self.base), self.base_pixels.as_mut()),
+            Layer::Foil => (self.foil.as_ref(), self.foil_pixels.as_mut()),
+            Layer::Etching => (self.etching.as_ref(), self.etching_pixels.as_mut()),
+            _ => (None, None),
+        };
+
+        let Some(texture) = texture else {
+            return Err(Error::MissingLayer { layer });
+        };
+
+        // Only compressed layers are left without pixels at this point
+        let Some(pixels) = pixels else {
+            return Err(Error::Compressed { layer });
+        };
+
+        write(queue, texture, pixels, layer, region, data)?;
+
+        if layer == Layer::Base && self.silhouette_alpha {
+            self.update_silhouette(queue)?;
+        }
+
+        Ok(())
+    }
+
+    // Computes the distance field of a `Silhouette::Alpha` again, for both
+    // the shader and hit testing
+    fn update_silhouette(&mut self, queue: &wgpu::Queue) -> Result<(), Error> {
+        let format = self.base.format();
+        let (width, height) = (self.base.width(), self.base.height());
+
+        let Some(pixels) = &mut self.base_pixels else {
+            return Ok(());
+        };
+
+        // The first level of the chain is the image itself
+        let rgba = &pixels.chain(format)[..width as usize * height as usize * 4];
+
+        let distances = Silhouette::Alpha.distances(&Image {
+            rgba: Bytes::copy_from_slice(rgba),
+            width,
+            height,
+            compression: None,
+        });
+
+        if let Some(texture) = &self._silhouette {
+            write(
+                queue,
+                texture,
+                &mut Pixels::Mask(distances.clone()),
+                Layer::Silhouette,
+                Region {
+                    x: 0,
+                    y: 0,
+                    width,
+                    height,
+                },
+                &distances.pixels,
+            )?;
+        }
+
+        self.silhouette = Some(distances);
+
+        Ok(())
+    }
+
+    /// Finds the point on the surface of the card under the given point of the
+    /// target, in the same coordinates as the [`Viewport`].
+    ///
+    /// It follows the camera, the outline and the shell of the shader, but
+    /// not the bumps of the relief.
+    pub fn hit_test(&self, parameters: Parameters, point: Point) -> Option<Hit> {
+        // More than the shader, to be precise on the edges
+        const MAX_ITERATIONS: u32 = 256;
+
         let Parameters { viewport, rotation } = parameters;
 
-        queue.write_buffer(
-            &self.instance,
-            0,
-            bytemuck::cast_slice(&[Instance {
-                viewport: [
-                    viewport.x as f32,
-                    viewport.y as f32,
-                    viewport.width as f32,
-                    viewport.height as f32,
-                ],
-                size: [self.width as f32, self.height as f32],
-                rotation: [rotation.a.x, rotation.a.y, rotation.a.z, rotation.w],
-            }]),
+        let geometry = Geometry::new(
+            self.width,
+            self.height,
+            self.shape,
+            self.shell,
+            (self.flags & FLAG_SLAB != 0).then_some(self.label),
+            self.silhouette.as_ref(),
         );
+
+        // The shader samples pixels from their top left corner
+        let (mut origin, mut direction) = raymarch::ray(viewport, point.x - 0.5, point.y - 0.5);
+        let mut t = -raymarch::MAX_DISTANCE;
+
+        if let Some(shell) = geometry.shell {
+            t = raymarch::march(
+                origin,
+                direction,
+                t,
+                rotation,
+                &geometry,
+                Surface::Shell,
+                MAX_ITERATIONS,
+            );
+
+            if t > 2.0 * raymarch::MAX_DISTANCE {
+                return None;
+            }
+
+            origin = origin + direction * t;
+
+            let normal = rotation.rotate(
+                geometry.estimate_normal(rotation.conjugate().rotate(origin), Surface::Shell),
+            );
+
+            direction = raymarch::refract(direction, normal, 1.0 / shell.refraction);
+            t = 0.0;
+        }
+
+        t = raymarch::march(
+            origin,
+            direction,
+            t,
+            rotation,
+            &geometry,
+            Surface::Card,
+            MAX_ITERATIONS,
+        );
+
+        if t > 2.0 * raymarch::MAX_DISTANCE {
+            return None;
+        }
+
+        let hit = rotation.conjugate().rotate(origin + direction * t);
+        let normal = geometry.estimate_normal(hit, Surface::Card);
+        let (u, v) = geometry.texture_uv(hit.x - geometry.offset.0, hit.y - geometry.offset.1);
+
+        let face = if normal.z.abs() > normal.x.abs() && normal.z.abs() > normal.y.abs() {
+            if normal.z < 0.0 {
+                Face::Front
+            } else {
+                Face::Back
+            }
+        } else {
+            Face::Edge
+        };
+
+        Some(Hit {
+            uv: match face {
+                Face::Back => [1.0 - u, v],
+                Face::Front | Face::Edge => [u, v],
+            },
+            face,
+            normal: rotation.rotate(normal),
+        })
+    }
+
+    /// Returns the layers that were downscaled to fit the limits of the
+    /// device, along with the scale applied to each of them.
+    pub fn downscaled(&self) -> &[(Layer, f32)] {
+        &self.downscaled
+    }
+
+    /// Returns the GPU memory taken by the textures and buffers of the card,
+    /// in bytes.
+    pub fn memory(&self) -> u64 {
+        let back = self
+            ._back
+            .iter()
+            .flat_map(|back| [Some(&back.base), back.foil.as_ref(), back.etching.as_ref()]);
+
+        [
+            Some(&self.base),
+            self.foil.as_ref(),
+            self.etching.as_ref(),
+            self._silhouette.as_ref(),
+            self._label.as_ref(),
+            self._wear.as_ref(),
+            self._relief.as_ref(),
+        ]
+        .into_iter()
+        .chain(back)
+        .flatten()
+        .map(texture_memory)
+        .sum::<u64>()
+            + self.instance.size()
+    }
+}
+
+fn texture_memory(texture: &wgpu::Texture) -> u64 {
+    let format = texture.format();
+    let (block_width, block_height) = format.block_dimensions();
+    let block_size = u64::from(format.block_copy_size(None).unwrap_or(4));
+
+    (0..texture.mip_level_count())
+        .map(|level| {
+            let size = texture
+                .size()
+                .mip_level_size(level, texture.dimension())
+                .physical_size(format);
+
+            u64::from(size.width / block_width)
+                * u64::from(size.height / block_height)
+                * u64::from(size.depth_or_array_layers)
+                * block_size
+        })
+        .sum()
+}
+
+fn write(
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    pixels: &mut Pixels,
+    layer: Layer,
+    region: Region,
+    data: &[u8],
+) -> Result<(), Error> {
+    let format = texture.format();
+    let bytes_per_pixel = format.block_copy_size(None).unwrap_or(4);
+
+    validate_region(
+        layer,
+        region,
+        texture.width(),
+        texture.height(),
+        bytes_per_pixel as usize,
+        data.len(),
+    )?;
+
+    let first = mipmap::Level {
+        x: region.x,
+        y: region.y,
+        width: region.width,
+        height: region.height,
+        data: data.to_vec(),
+    };
+
+    let levels = mipmap::update(
+        pixels.chain(format),
+        texture.width(),
+        texture.height(),
+        format,
+        first,
+    );
+
+    for (mip_level, level) in levels.into_iter().enumerate() {
+        queue.write_texture(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: mip_level as u32,
+                origin: wgpu::Origin3d {
+                    x: level.x,
+                    y: level.y,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &level.data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(level.width * bytes_per_pixel),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width: level.width,
+                height: level.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// The pixels of a texture that can be updated.
+///
+/// Only textures uploaded as they are, without downscaling or compression,
+/// keep their pixels. Updates rebuild mip texels from their neighbors, so the whole mip chain
+/// is kept on the CPU after the first one.
+#[derive(Debug)]
+pub(crate) enum Pixels {
+    Image(Image),
+    Mask(Mask),
+    Chain(Vec<u8>),
+}
+
+impl Pixels {
+    fn chain(&mut self, format: wgpu::TextureFormat) -> &mut [u8] {
+        let chain = match self {
+            Pixels::Image(image) => {
+                mipmap::chain(image.width, image.height, format, &image.decompressed())
+            }
+            Pixels::Mask(mask) => {
+                mipmap::chain(mask.width, mask.height, format, &mask.decompressed())
+            }
+            Pixels::Chain(chain) => return chain,
+        };
+
+        *self = Pixels::Chain(chain);
+        self.chain(format)
     }
 }
 
@@ -37,71 +466,780 @@ pub struct Structure {
     pub base: Image,
     pub foil: Option<Mask>,
     pub etching: Option<Mask>,
-    pub width: u32,
+    pub wear: Option<Mask>,
+    pub relief: Option<Relief>,
+    pub back: Option<Back>,
+    pub silhouette: Option<Silhouette>,
+    pub shape: Shape,
+    pub sleeve: Option<Sleeve>,
+    pub slab: Option<Slab>,
+}
+
+impl Structure {
+    /// Creates a [`Structure`] with the given base and no other layers.
+    ///
+    /// Combine it with struct update syntax to add layers, like
+    /// `Structure { foil: Some(foil), ..Structure::new(base) }`.
+    pub fn new(base: Image) -> Self {
+        Self {
+            base,
+            foil: None,
+            etching: None,
+            wear: None,
+            relief: None,
+            back: None,
+            silhouette: None,
+            shape: Shape::default(),
+            sleeve: None,
+            slab: None,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        self.base.validate(Layer::Base)?;
+
+        let masks = [
+            (Layer::Foil, &self.foil),
+            (Layer::Etching, &self.etching),
+            (Layer::Wear, &self.wear),
+        ];
+
+        for (layer, mask) in masks {
+            if let Some(mask) = mask {
+                mask.validate(layer)?;
+            }
+        }
+
+        match &self.relief {
+            Some(Relief::Height { map, .. }) => map.validate(Layer::Relief)?,
+            Some(Relief::Normal(image)) => image.validate(Layer::Relief)?,
+            None => {}
+        }
+
+        if let Some(Silhouette::Mask(mask)) = &self.silhouette {
+            mask.validate(Layer::Silhouette)?;
+        }
+
+        if let Some(slab) = &self.slab {
+            slab.label.validate(Layer::Label)?;
+        }
+
+        if let Some(back) = &self.back {
+            back.base.validate(Layer::BackBase)?;
+
+            if let Some(foil) = &back.foil {
+                foil.validate(Layer::BackFoil)?;
+            }
+
+            if let Some(etching) = &back.etching {
+                etching.validate(Layer::BackEtching)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Downscales the textures that exceed the given dimension, returning the
+    /// scale applied to each of them.
+    pub(crate) fn fit(&self, limit: u32) -> (Structure, Vec<(Layer, f32)>) {
+        let mut scales = Vec::new();
+
+        let base = fitted(&mut scales, Layer::Base, self.base.fit(limit, true));
+        let foil = self
+            .foil
+            .as_ref()
+            .map(|foil| fitted(&mut scales, Layer::Foil, foil.fit(limit)));
+        let etching = self
+            .etching
+            .as_ref()
+            .map(|etching| fitted(&mut scales, Layer::Etching, etching.fit(limit)));
+        let wear = self
+            .wear
+            .as_ref()
+            .map(|wear| fitted(&mut scales, Layer::Wear, wear.fit(limit)));
+        let relief = self
+            .relief
+            .as_ref()
+            .map(|relief| fitted(&mut scales, Layer::Relief, relief.fit(limit)));
+        let silhouette = self.silhouette.as_ref().map(|silhouette| match silhouette {
+            Silhouette::Alpha => Silhouette::Alpha,
+            Silhouette::Mask(mask) => {
+                Silhouette::Mask(fitted(&mut scales, Layer::Silhouette, mask.fit(limit)))
+            }
+        });
+        let slab = self.slab.as_ref().map(|slab| Slab {
+            label: fitted(&mut scales, Layer::Label, slab.label.fit(limit, true)),
+            ..slab.clone()
+        });
+        let back = self.back.as_ref().map(|back| Back {
+            base: fitted(&mut scales, Layer::BackBase, back.base.fit(limit, true)),
+            foil: back
+                .foil
+                .as_ref()
+                .map(|foil| fitted(&mut scales, Layer::BackFoil, foil.fit(limit))),
+            etching: back
+                .etching
+                .as_ref()
+                .map(|etching| fitted(&mut scales, Layer::BackEtching, etching.fit(limit))),
+        });
+
+        let structure = Structure {
+            base,
+            foil,
+            etching,
+            wear,
+            relief,
+            back,
+            silhouette,
+            shape: self.shape,
+            sleeve: self.sleeve,
+            slab,
+        };
+
+        (structure, scales)
+    }
+}
+
+fn fitted<T>(scales: &mut Vec<(Layer, f32)>, layer: Layer, (texture, scale): (T, f32)) -> T {
+    if scale < 1.0 {
+        scales.push((layer, scale));
+    }
+
+    texture
+}
+
+/// A texture of a [`Structure`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Base,
+    Foil,
+    Etching,
+    Wear,
+    Relief,
+    Silhouette,
+    Label,
+    BackBase,
+    BackFoil,
+    BackEtching,
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Layer::Base => "base",
+            Layer::Foil => "foil",
+            Layer::Etching => "etching",
+            Layer::Wear => "wear",
+            Layer::Relief => "relief",
+            Layer::Silhouette => "silhouette",
+            Layer::Label => "label",
+            Layer::BackBase => "back base",
+            Layer::BackFoil => "back foil",
+            Layer::BackEtching => "back etching",
+        })
+    }
+}
+
+/// The outline of a card.
+///
+/// Every length is relative to the width of the card.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    RoundedRectangle { radius: f32 },
+    Circle { ridges: u32 },
+    Polygon { sides: u32, radius: f32 },
+    Capsule,
+}
+
+impl Shape {
+    // Must match the `SHAPE_*` constants in the shader
+    fn encode(self) -> (u32, [f32; 2]) {
+        match self {
+            Shape::RoundedRectangle { radius } => (0, [radius, 0.0]),
+            Shape::Circle { ridges } => (1, [ridges as f32, 0.0]),
+            Shape::Polygon { sides, radius } => (2, [sides.max(3) as f32, radius]),
+            Shape::Capsule => (3, [0.0, 0.0]),
+        }
+    }
+}
+
+impl Default for Shape {
+    fn default() -> Self {
+        Self::RoundedRectangle { radius: 0.025 }
+    }
+}
+
+/// The material stack of the back face of a card.
+#[derive(Debug, Clone)]
+pub struct Back {
+    pub base: Image,
+    pub foil: Option<Mask>,
+    pub etching: Option<Mask>,
+}
+
+impl Back {
+    pub(crate) fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Textures {
+        Textures {
+            base: self.base.upload(device, queue),
+            foil: self.foil.as_ref().map(|foil| foil.upload(device, queue)),
+            etching: self
+                .etching
+                .as_ref()
+                .map(|etching| etching.upload(device, queue)),
+        }
+    }
+}
+
+impl From<Image> for Back {
+    fn from(base: Image) -> Self {
+        Self {
+            base,
+            foil: None,
+            etching: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Textures {
+    pub base: wgpu::Texture,
+    pub foil: Option<wgpu::Texture>,
+    pub etching: Option<wgpu::Texture>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Image {
+    /// The RGBA pixels, or the compressed blocks if there is a [`Compression`].
     pub rgba: Bytes,
-    pub size: u32,
+    pub width: u32,
+    pub height: u32,
+    pub compression: Option<Compression>,
 }
 
 impl Image {
     pub(crate) fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
-        use wgpu::util::DeviceExt;
-
-        device.create_texture_with_data(
+        upload(
+            device,
             queue,
-            &wgpu::TextureDescriptor {
-                label: None,
-                size: wgpu::Extent3d {
-                    width: self.size,
-                    height: self.size,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[],
-            },
-            wgpu::util::TextureDataOrder::LayerMajor,
+            self.width,
+            self.height,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            self.compression,
             &self.rgba,
         )
     }
+
+    pub(crate) fn validate(&self, layer: Layer) -> Result<(), Error> {
+        validate(
+            layer,
+            self.width,
+            self.height,
+            4,
+            self.compression,
+            self.rgba.len(),
+        )
+    }
+
+    pub(crate) fn fit(&self, limit: u32, is_srgb: bool) -> (Image, f32) {
+        let format = if is_srgb {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+
+        match fit(self.width, self.height, limit, format, || {
+            self.decompressed()
+        }) {
+            Some((width, height, rgba, scale)) => (
+                Image {
+                    rgba: Bytes::from(rgba),
+                    width,
+                    height,
+                    compression: None,
+                },
+                scale,
+            ),
+            None => (self.clone(), 1.0),
+        }
+    }
+
+    pub(crate) fn decompressed(&self) -> Cow<'_, [u8]> {
+        match self.compression {
+            Some(compression) => {
+                Cow::Owned(compression.decompress(self.width, self.height, true, &self.rgba))
+            }
+            None => Cow::Borrowed(&self.rgba),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Mask {
+    /// A byte per pixel, or the compressed blocks if there is a
+    /// [`Compression`], whose red channel is the mask.
+    ///
+    /// Masks can only be compressed with single channel formats or ASTC.
     pub pixels: Bytes,
-    pub size: u32,
+    pub width: u32,
+    pub height: u32,
+    pub compression: Option<Compression>,
 }
 
 impl Mask {
     pub(crate) fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
-        use wgpu::util::DeviceExt;
-
-        device.create_texture_with_data(
+        upload(
+            device,
             queue,
-            &wgpu::TextureDescriptor {
-                label: None,
-                size: wgpu::Extent3d {
-                    width: self.size,
-                    height: self.size,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::R8Unorm,
-                usage: wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[],
-            },
-            wgpu::util::TextureDataOrder::LayerMajor,
+            self.width,
+            self.height,
+            wgpu::TextureFormat::R8Unorm,
+            self.compression,
             &self.pixels,
         )
     }
+
+    fn validate(&self, layer: Layer) -> Result<(), Error> {
+        validate(
+            layer,
+            self.width,
+            self.height,
+            1,
+            self.compression,
+            self.pixels.len(),
+        )
+    }
+
+    fn fit(&self, limit: u32) -> (Mask, f32) {
+        let format = wgpu::TextureFormat::R8Unorm;
+
+        match fit(self.width, self.height, limit, format, || {
+            self.decompressed()
+        }) {
+            Some((width, height, pixels, scale)) => (
+                Mask {
+                    pixels: Bytes::from(pixels),
+                    width,
+                    height,
+                    compression: None,
+                },
+                scale,
+            ),
+            None => (self.clone(), 1.0),
+        }
+    }
+
+    pub(crate) fn decompressed(&self) -> Cow<'_, [u8]> {
+        match self.compression {
+            Some(compression) => Cow::Owned(red(&compression.decompress(
+                self.width,
+                self.height,
+                false,
+                &self.pixels,
+            ))),
+            None => Cow::Borrowed(&self.pixels),
+        }
+    }
+}
+
+fn validate(
+    layer: Layer,
+    width: u32,
+    height: u32,
+    bytes_per_pixel: usize,
+    compression: Option<Compression>,
+    length: usize,
+) -> Result<(), Error> {
+    if width == 0 || height == 0 {
+        return Err(Error::Empty { layer });
+    }
+
+    if let Some(compression) = compression
+        && !compression.fits(bytes_per_pixel)
+    {
+        return Err(Error::InvalidCompression { layer, compression });
+    }
+
+    let expected = match compression {
+        Some(compression) => compression.chain_size(width, height),
+        None => width as usize * height as usize * bytes_per_pixel,
+    };
+
+    if let Some(compression) = compression
+        && length < expected
+        && length >= compression.size(width, height)
+    {
+        return Err(Error::MissingMipLevels {
+            layer,
+            expected: mipmap::count(width, height),
+            actual: compression.levels(width, height, length),
+        });
+    }
+
+    if length != expected {
+        return Err(Error::InvalidLength {
+            layer,
+            expected,
+            actual: length,
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_region(
+    layer: Layer,
+    region: Region,
+    width: u32,
+    height: u32,
+    bytes_per_pixel: usize,
+    length: usize,
+) -> Result<(), Error> {
+    let fits = |start: u32, length: u32, size: u32| {
+        length > 0 && start.checked_add(length).is_some_and(|end| end <= size)
+    };
+
+    if !fits(region.x, region.width, width) || !fits(region.y, region.height, height) {
+        return Err(Error::InvalidRegion { layer, region });
+    }
+
+    let expected = region.width as usize * region.height as usize * bytes_per_pixel;
+
+    if length != expected {
+        return Err(Error::InvalidLength {
+            layer,
+            expected,
+            actual: length,
+        });
+    }
+
+    Ok(())
+}
+
+// Resamples the pixels of a texture exceeding the given dimension, keeping its
+// aspect ratio
+fn fit<'a>(
+    width: u32,
+    height: u32,
+    limit: u32,
+    format: wgpu::TextureFormat,
+    pixels: impl FnOnce() -> Cow<'a, [u8]>,
+) -> Option<(u32, u32, Vec<u8>, f32)> {
+    if width <= limit && height <= limit {
+        return None;
+    }
+
+    let scale = limit as f32 / width.max(height) as f32;
+    let new_width = ((width as f32 * scale).round() as u32).clamp(1, limit);
+    let new_height = ((height as f32 * scale).round() as u32).clamp(1, limit);
+
+    let resized = mipmap::resize(width, height, new_width, new_height, format, &pixels());
+
+    Some((new_width, new_height, resized, scale))
+}
+
+fn red(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks_exact(4).map(|pixel| pixel[0]).collect()
+}
+
+/// The relief of an embossed or textured card surface.
+#[derive(Debug, Clone)]
+pub enum Relief {
+    /// A height map, where a value of `255` rises `strength` pixels above `0`.
+    Height { map: Mask, strength: f32 },
+    /// A tangent space normal map, with the green channel pointing up.
+    Normal(Image),
+}
+
+impl Relief {
+    pub(crate) fn upload(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
+        let (width, height, normals) = match self {
+            Relief::Height { map, strength } => (map.width, map.height, normal_map(map, *strength)),
+            Relief::Normal(image) => (image.width, image.height, image.rgba.clone()),
+        };
+
+        let compression = match self {
+            Relief::Height { .. } => None,
+            Relief::Normal(image) => image.compression,
+        };
+
+        // Normals are not colors!
+        upload(
+            device,
+            queue,
+            width,
+            height,
+            wgpu::TextureFormat::Rgba8Unorm,
+            compression,
+            &normals,
+        )
+    }
+
+    fn fit(&self, limit: u32) -> (Relief, f32) {
+        match self {
+            // Heights are measured in pixels
+            Relief::Height { map, strength } => {
+                let (map, scale) = map.fit(limit);
+
+                (
+                    Relief::Height {
+                        map,
+                        strength: strength * scale,
+                    },
+                    scale,
+                )
+            }
+            Relief::Normal(image) => {
+                let (image, scale) = image.fit(limit, false);
+
+                (Relief::Normal(image), scale)
+            }
+        }
+    }
+}
+
+pub(crate) fn normal_map(map: &Mask, strength: f32) -> Bytes {
+    let width = map.width as usize;
+    let height = map.height as usize;
+    let max_x = width.saturating_sub(1);
+    let max_y = height.saturating_sub(1);
+
+    let pixels = map.decompressed();
+    let sample = |x: usize, y: usize| f32::from(pixels[y * width + x]) / 255.0;
+    let mut normals = Vec::with_capacity(width * height * 4);
+
+    for y in 0..height {
+        for x in 0..width {
+            let dx = (sample((x + 1).min(max_x), y) - sample(x.saturating_sub(1), y)) / 2.0;
+            let dy = (sample(x, (y + 1).min(max_y)) - sample(x, y.saturating_sub(1))) / 2.0;
+
+            // Rows grow downwards, but the normal map points up
+            let normal = Vector {
+                x: -dx * strength,
+                y: dy * strength,
+                z: 1.0,
+            };
+
+            let normal = normal / normal.dot(normal).sqrt();
+            let encode = |value: f32| ((value * 0.5 + 0.5) * 255.0).round() as u8;
+
+            normals.extend([encode(normal.x), encode(normal.y), encode(normal.z), 255]);
+        }
+    }
+
+    Bytes::from(normals)
+}
+
+/// A transparent plastic shell around a card.
+///
+/// Every length is relative to the width of the card.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sleeve {
+    pub margin: f32,
+    pub thickness: f32,
+    pub refraction: f32,
+    pub gloss: f32,
+    pub tint: [f32; 4],
+}
+
+impl Sleeve {
+    pub const PENNY: Self = Self {
+        margin: 0.015,
+        thickness: 0.002,
+        refraction: 1.45,
+        gloss: 0.6,
+        tint: [0.9, 0.92, 0.95, 0.2],
+    };
+
+    pub const TOPLOADER: Self = Self {
+        margin: 0.04,
+        thickness: 0.015,
+        refraction: 1.5,
+        gloss: 0.9,
+        tint: [0.75, 0.85, 0.9, 0.6],
+    };
+
+    pub const ACRYLIC: Self = Self {
+        margin: 0.05,
+        thickness: 0.06,
+        refraction: 1.49,
+        gloss: 1.0,
+        tint: [0.85, 0.9, 0.92, 0.5],
+    };
+
+    pub(crate) const NONE: Self = Self {
+        margin: 0.0,
+        thickness: 0.0,
+        refraction: 1.0,
+        gloss: 0.0,
+        tint: [0.0; 4],
+    };
+}
+
+/// An acrylic case with a label on top, like the ones of graded cards.
+///
+/// A slab replaces the [`Sleeve`] of a card, if any.
+#[derive(Debug, Clone)]
+pub struct Slab {
+    pub label: Image,
+    pub label_height: f32,
+    pub acrylic: Sleeve,
+}
+
+impl Slab {
+    pub fn new(label: Image) -> Self {
+        Self {
+            label,
+            label_height: 0.3,
+            acrylic: Sleeve::ACRYLIC,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Silhouette {
+    Alpha,
+    Mask(Mask),
+}
+
+impl Silhouette {
+    // Must match `silhouette_spread` in the shader
+    pub(crate) const SPREAD: f32 = 16.0;
+
+    /// Computes the signed distance field of the silhouette.
+    pub(crate) fn distances(&self, base: &Image) -> Mask {
+        let (width, height, distances) = match self {
+            Silhouette::Alpha => {
+                let rgba = base.decompressed();
+
+                (
+                    base.width,
+                    base.height,
+                    distance_field(base.width, base.height, |i| rgba[i * 4 + 3] >= 128),
+                )
+            }
+            Silhouette::Mask(mask) => {
+                let pixels = mask.decompressed();
+
+                (
+                    mask.width,
+                    mask.height,
+                    distance_field(mask.width, mask.height, |i| pixels[i] >= 128),
+                )
+            }
+        };
+
+        Mask {
+            pixels: Bytes::from(distances),
+            width,
+            height,
+            compression: None,
+        }
+    }
+}
+
+// Signed distance field encoded in a byte per pixel, where values below 128
+// are inside the shape
+fn distance_field(width: u32, height: u32, is_inside: impl Fn(usize) -> bool) -> Vec<u8> {
+    let width = width as usize;
+    let height = height as usize;
+    let n_pixels = width * height;
+
+    let mut outside = vec![0.0; n_pixels];
+    let mut inside = vec![0.0; n_pixels];
+
+    for i in 0..n_pixels {
+        if is_inside(i) {
+            outside[i] = INFINITY;
+        } else {
+            inside[i] = INFINITY;
+        }
+    }
+
+    // Squared distance to the nearest pixel of the opposite set
+    squared_distance_transform(&mut outside, width, height);
+    squared_distance_transform(&mut inside, width, height);
+
+    outside
+        .into_iter()
+        .zip(inside)
+        .map(|(outside, inside)| {
+            let distance = if inside > 0.0 {
+                inside.sqrt() - 0.5
+            } else {
+                0.5 - outside.sqrt()
+            };
+
+            let value = 0.5 + distance / (2.0 * Silhouette::SPREAD);
+
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        })
+        .collect()
+}
+
+const INFINITY: f32 = 1e20;
+
+// Felzenszwalb & Huttenlocher's exact Euclidean distance transform
+fn squared_distance_transform(grid: &mut [f32], width: usize, height: usize) {
+    let size = width.max(height);
+
+    let mut f = vec![0.0; size];
+    let mut d = vec![0.0; size];
+    let mut v = vec![0; size];
+    let mut z = vec![0.0; size + 1];
+
+    for x in 0..width {
+        for y in 0..height {
+            f[y] = grid[y * width + x];
+        }
+
+        transform_1d(&f[..height], &mut d[..height], &mut v, &mut z);
+
+        for y in 0..height {
+            grid[y * width + x] = d[y];
+        }
+    }
+
+    for y in 0..height {
+        let row = &mut grid[y * width..(y + 1) * width];
+
+        f[..width].copy_from_slice(row);
+        transform_1d(&f[..width], &mut d[..width], &mut v, &mut z);
+        row.copy_from_slice(&d[..width]);
+    }
+}
+
+fn transform_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+    let parabola = |q: usize| f[q] + (q * q) as f32;
+
+    let mut k = 0;
+    v[0] = 0;
+    z[0] = -INFINITY;
+    z[1] = INFINITY;
+
+    for q in 1..f.len() {
+        let mut s = (parabola(q) - parabola(v[k])) / (2 * (q - v[k])) as f32;
+
+        while s <= z[k] {
+            k -= 1;
+            s = (parabola(q) - parabola(v[k])) / (2 * (q - v[k])) as f32;
+        }
+
+        k += 1;
+        v[k] = q;
+        z[k] = s;
+        z[k + 1] = INFINITY;
+    }
+
+    k = 0;
+
+    for (q, d) in d.iter_mut().enumerate() {
+        while z[k + 1] < q as f32 {
+            k += 1;
+        }
+
+        let delta = q as f32 - v[k] as f32;
+        *d = delta * delta + f[v[k]];
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -118,10 +1256,335 @@ pub struct Viewport {
     pub height: u32,
 }
 
-#[derive(Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
+/// A point of the render target, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+/// A point on the surface of a [`Card`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    /// The texture coordinates of the point on its face, with the origin on
+    /// the top left corner.
+    ///
+    /// The edge uses the coordinates of the front.
+    pub uv: [f32; 2],
+    pub face: Face,
+    /// The normal of the surface, after rotating the card.
+    pub normal: Vector,
+}
+
+/// A face of a [`Card`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Face {
+    Front,
+    Back,
+    Edge,
+}
+
+/// A rectangle of pixels inside of a texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+fn upload(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    compression: Option<Compression>,
+    data: &[u8],
+) -> wgpu::Texture {
+    use wgpu::util::DeviceExt;
+
+    let (format, levels, data) = match compression {
+        Some(compression) if compression.is_supported(device, width, height) => (
+            compression.format(format.is_srgb()),
+            mipmap::count(width, height),
+            Cow::Borrowed(data),
+        ),
+        Some(compression) => {
+            let rgba = compression.decompress(width, height, format.is_srgb(), data);
+            let pixels = if format == wgpu::TextureFormat::R8Unorm {
+                red(&rgba)
+            } else {
+                rgba
+            };
+
+            (
+                format,
+                mipmap::count(width, height),
+                Cow::Owned(mipmap::chain(width, height, format, &pixels)),
+            )
+        }
+        None => (
+            format,
+            mipmap::count(width, height),
+            Cow::Owned(mipmap::chain(width, height, format, data)),
+        ),
+    };
+
+    device.create_texture_with_data(
+        queue,
+        &wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: levels,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        },
+        wgpu::util::TextureDataOrder::LayerMajor,
+        &data,
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, bytemuck::Zeroable, bytemuck::Pod)]
 #[repr(C)]
 pub struct Instance {
     viewport: [f32; 4],
     size: [f32; 2],
     rotation: [f32; 4],
+    flags: u32,
+    shape: u32,
+    shape_parameters: [f32; 2],
+    shell: [f32; 4],
+    shell_tint: [f32; 4],
+    label: f32,
+}
+
+pub(crate) const FLAG_SILHOUETTE: u32 = 1 << 0;
+pub(crate) const FLAG_SHELL: u32 = 1 << 1;
+pub(crate) const FLAG_SLAB: u32 = 1 << 2;
+pub(crate) const FLAG_WEAR: u32 = 1 << 3;
+pub(crate) const FLAG_RELIEF: u32 = 1 << 4;
+pub(crate) const FLAG_BACK: u32 = 1 << 5;
+pub(crate) const FLAG_FOIL: u32 = 1 << 6;
+pub(crate) const FLAG_ETCHING: u32 = 1 << 7;
+pub(crate) const FLAG_BACK_FOIL: u32 = 1 << 8;
+pub(crate) const FLAG_BACK_ETCHING: u32 = 1 << 9;
+
+// The amount of textures bound to each card
+pub(crate) const TEXTURES: usize = 10;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32) -> Image {
+        Image {
+            rgba: Bytes::from(vec![255; (width * height * 4) as usize]),
+            width,
+            height,
+            compression: None,
+        }
+    }
+
+    fn mask(width: u32, height: u32) -> Mask {
+        Mask {
+            pixels: Bytes::from(vec![255; (width * height) as usize]),
+            width,
+            height,
+            compression: None,
+        }
+    }
+
+    #[test]
+    fn empty_textures() {
+        assert_eq!(
+            image(0, 10).validate(Layer::Base),
+            Err(Error::Empty { layer: Layer::Base })
+        );
+        assert_eq!(
+            mask(10, 0).validate(Layer::Wear),
+            Err(Error::Empty { layer: Layer::Wear })
+        );
+    }
+
+    #[test]
+    fn invalid_lengths() {
+        let image = Image {
+            rgba: Bytes::from(vec![0; 10 * 10 * 3]),
+            ..image(10, 10)
+        };
+
+        assert_eq!(
+            image.validate(Layer::BackBase),
+            Err(Error::InvalidLength {
+                layer: Layer::BackBase,
+                expected: 400,
+                actual: 300,
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_compression() {
+        let image = Image {
+            compression: Some(Compression::Bc4),
+            ..image(16, 16)
+        };
+        let mask = Mask {
+            compression: Some(Compression::Etc2),
+            ..mask(16, 16)
+        };
+
+        assert_eq!(
+            image.validate(Layer::Base),
+            Err(Error::InvalidCompression {
+                layer: Layer::Base,
+                compression: Compression::Bc4,
+            })
+        );
+        assert_eq!(
+            mask.validate(Layer::Etching),
+            Err(Error::InvalidCompression {
+                layer: Layer::Etching,
+                compression: Compression::Etc2,
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_regions() {
+        let region = |x, y, width, height| Region {
+            x,
+            y,
+            width,
+            height,
+        };
+
+        for region in [
+            region(0, 0, 0, 4),
+            region(0, 0, 4, 0),
+            region(8, 0, 3, 1),
+            region(0, 5, 1, 2),
+            region(u32::MAX, 0, 2, 1),
+        ] {
+            assert_eq!(
+                validate_region(Layer::Foil, region, 10, 6, 1, 0),
+                Err(Error::InvalidRegion {
+                    layer: Layer::Foil,
+                    region,
+                })
+            );
+        }
+
+        assert_eq!(
+            validate_region(Layer::Base, region(7, 0, 3, 6), 10, 6, 4, 3 * 6 * 4),
+            Ok(())
+        );
+        assert_eq!(
+            validate_region(Layer::Base, region(7, 0, 3, 6), 10, 6, 4, 3 * 6),
+            Err(Error::InvalidLength {
+                layer: Layer::Base,
+                expected: 3 * 6 * 4,
+                actual: 3 * 6,
+            })
+        );
+    }
+
+    #[test]
+    fn fit_clamps_the_largest_dimension() {
+        for (width, height, expected) in [
+            (300, 100, (64, 21)),
+            (100, 300, (21, 64)),
+            (500, 500, (64, 64)),
+            (1000, 3, (64, 1)),
+        ] {
+            let (fitted, scale) = image(width, height).fit(64, true);
+
+            assert_eq!((fitted.width, fitted.height), expected);
+            assert_eq!(fitted.rgba.len(), (expected.0 * expected.1 * 4) as usize);
+            assert_eq!(scale, 64.0 / width.max(height) as f32);
+        }
+    }
+
+    #[test]
+    fn fit_keeps_the_aspect_ratio() {
+        for (width, height) in [(733, 1024), (50, 700), (1920, 1080)] {
+            let (fitted, _) = mask(width, height).fit(128);
+
+            // Up to the rounding of the smallest dimension
+            let ratio = width as f32 / height as f32;
+            let fitted_ratio = fitted.width as f32 / fitted.height as f32;
+            let tolerance = ratio.max(1.0) / fitted.width.min(fitted.height) as f32;
+
+            assert!(
+                (ratio - fitted_ratio).abs() <= tolerance,
+                "{width}x{height} -> {}x{}",
+                fitted.width,
+                fitted.height
+            );
+        }
+    }
+
+    #[test]
+    fn fit_reports_downscaled_layers() {
+        let structure = Structure {
+            foil: Some(mask(300, 420)),
+            etching: Some(mask(100, 140)),
+            ..Structure::new(image(100, 140))
+        };
+
+        let (fitted, downscaled) = structure.fit(200);
+
+        assert_eq!(downscaled, [(Layer::Foil, 200.0 / 420.0)]);
+        assert_eq!((fitted.base.width, fitted.base.height), (100, 140));
+
+        let foil = fitted.foil.unwrap();
+        assert_eq!((foil.width, foil.height), (143, 200));
+
+        // Layers within the limits are not resized
+        let (_, downscaled) = structure.fit(420);
+        assert!(downscaled.is_empty());
+    }
+
+    #[test]
+    fn compressed_payloads_need_every_mip_level() {
+        // 16x24, 8x12, 4x6, 2x3 and 1x1 pixels in 24, 6, 2, 1 and 1 blocks
+        let mask = |blocks: usize| Mask {
+            pixels: Bytes::from(vec![0; blocks * 8]),
+            width: 16,
+            height: 24,
+            compression: Some(Compression::Bc4),
+        };
+
+        assert_eq!(mask(34).validate(Layer::Foil), Ok(()));
+
+        for (blocks, actual) in [(24, 1), (30, 2), (33, 4)] {
+            assert_eq!(
+                mask(blocks).validate(Layer::Foil),
+                Err(Error::MissingMipLevels {
+                    layer: Layer::Foil,
+                    expected: 5,
+                    actual,
+                })
+            );
+        }
+
+        for blocks in [23, 35] {
+            assert_eq!(
+                mask(blocks).validate(Layer::Foil),
+                Err(Error::InvalidLength {
+                    layer: Layer::Foil,
+                    expected: 34 * 8,
+                    actual: blocks * 8,
+                })
+            );
+        }
+    }
 }
@@ -0,0 +1,696 @@
+mod astc;
+mod bc4;
+mod bc7;
+mod etc2;
+
+/// A block compressed texture payload.
+///
+/// Every block takes 8 bytes with [`Bc4`] and [`EacR11`], and 16 bytes
+/// otherwise. Blocks are laid out row by row. A payload contains every mip
+/// level, from the largest to the smallest, one after the other, down to a
+/// single pixel.
+///
+/// Images take [`Bc7`], [`Etc2`] or [`Astc`], while masks take [`Bc4`],
+/// [`EacR11`] or [`Astc`]; color formats would spend four times the memory on
+/// a single channel.
+///
+/// Compressed payloads are uploaded as they are when the device supports
+/// their format and the dimensions of the texture are multiples of the block
+/// size. Otherwise, they are decompressed on the CPU.
+///
+/// [`Bc7`]: Compression::Bc7
+/// [`Bc4`]: Compression::Bc4
+/// [`Etc2`]: Compression::Etc2
+/// [`EacR11`]: Compression::EacR11
+/// [`Astc`]: Compression::Astc
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// BC7, in blocks of 4x4 pixels.
+    Bc7,
+    /// BC4, a single channel in blocks of 4x4 pixels.
+    Bc4,
+    /// ETC2 with EAC alpha, in blocks of 4x4 pixels.
+    Etc2,
+    /// EAC R11, a single channel in blocks of 4x4 pixels.
+    EacR11,
+    /// ASTC with low dynamic range, in blocks of the given size.
+    Astc(wgpu::AstcBlock),
+}
+
+impl Compression {
+    pub(crate) fn format(self, is_srgb: bool) -> wgpu::TextureFormat {
+        match self {
+            Compression::Bc7 if is_srgb => wgpu::TextureFormat::Bc7RgbaUnormSrgb,
+            Compression::Bc7 => wgpu::TextureFormat::Bc7RgbaUnorm,
+            Compression::Bc4 => wgpu::TextureFormat::Bc4RUnorm,
+            Compression::Etc2 if is_srgb => wgpu::TextureFormat::Etc2Rgba8UnormSrgb,
+            Compression::Etc2 => wgpu::TextureFormat::Etc2Rgba8Unorm,
+            Compression::EacR11 => wgpu::TextureFormat::EacR11Unorm,
+            Compression::Astc(block) => wgpu::TextureFormat::Astc {
+                block,
+                channel: if is_srgb {
+                    wgpu::AstcChannel::UnormSrgb
+                } else {
+                    wgpu::AstcChannel::Unorm
+                },
+            },
+        }
+    }
+
+    /// Whether the format fits textures with the given amount of channels.
+    pub(crate) fn fits(self, channels: usize) -> bool {
+        match self {
+            Compression::Bc7 | Compression::Etc2 => channels == 4,
+            Compression::Bc4 | Compression::EacR11 => channels == 1,
+            Compression::Astc(_) => true,
+        }
+    }
+
+    pub(crate) fn is_supported(self, device: &wgpu::Device, width: u32, height: u32) -> bool {
+        let format = self.format(false);
+        let (block_width, block_height) = format.block_dimensions();
+
+        device.features().contains(format.required_features())
+            && width.is_multiple_of(block_width)
+            && height.is_multiple_of(block_height)
+    }
+
+    /// Counts the whole mip levels contained in a payload of the given length.
+    pub(crate) fn levels(self, width: u32, height: u32, length: usize) -> u32 {
+        let mut consumed = 0;
+        let mut levels = 0;
+
+        for level in 0..crate::mipmap::count(width, height) {
+            consumed += self.size((width >> level).max(1), (height >> level).max(1));
+
+            if consumed > length {
+                break;
+            }
+
+            levels += 1;
+        }
+
+        levels
+    }
+
+    /// The amount of bytes of a whole mip chain.
+    pub(crate) fn chain_size(self, width: u32, height: u32) -> usize {
+        (0..crate::mipmap::count(width, height))
+            .map(|level| self.size((width >> level).max(1), (height >> level).max(1)))
+            .sum()
+    }
+
+    /// The amount of bytes of a single mip level.
+    pub(crate) fn size(self, width: u32, height: u32) -> usize {
+        let format = self.format(false);
+        let (block_width, block_height) = format.block_dimensions();
+        let block_size = format.block_copy_size(None).unwrap();
+
+        width.div_ceil(block_width) as usize
+            * height.div_ceil(block_height) as usize
+            * block_size as usize
+    }
+
+    /// Decompresses the first mip level of a payload into RGBA pixels.
+    ///
+    /// Single channel formats fill the red channel, like sampling them does.
+    pub(crate) fn decompress(self, width: u32, height: u32, is_srgb: bool, data: &[u8]) -> Vec<u8> {
+        let format = self.format(false);
+        let (block_width, block_height) = format.block_dimensions();
+        let (block_width, block_height) = (block_width as usize, block_height as usize);
+        let block_size = format.block_copy_size(None).unwrap() as usize;
+        let (width, height) = (width as usize, height as usize);
+
+        let blocks_per_row = width.div_ceil(block_width);
+        let mut rgba = vec![0; width * height * 4];
+        let mut texels = vec![[0; 4]; block_width * block_height];
+
+        for (i, block) in data
+            .chunks_exact(block_size)
+            .take(blocks_per_row * height.div_ceil(block_height))
+            .enumerate()
+        {
+            match self {
+                Compression::Bc7 => bc7::decode(block.try_into().unwrap(), &mut texels),
+                Compression::Bc4 => bc4::decode(block.try_into().unwrap(), &mut texels),
+                Compression::Etc2 => etc2::decode(block.try_into().unwrap(), &mut texels),
+                Compression::EacR11 => etc2::decode_r11(block.try_into().unwrap(), &mut texels),
+                Compression::Astc(_) => astc::decode(
+                    block.try_into().unwrap(),
+                    block_width,
+                    block_height,
+                    is_srgb,
+                    &mut texels,
+                ),
+            }
+
+            let x = i % blocks_per_row * block_width;
+            let y = i / blocks_per_row * block_height;
+
+            for (j, texel) in texels.iter().enumerate() {
+                let (x, y) = (x + j % block_width, y + j / block_width);
+
+                if x < width && y < height {
+                    let offset = (y * width + x) * 4;
+                    rgba[offset..offset + 4].copy_from_slice(texel);
+                }
+            }
+        }
+
+        rgba
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A block and its texels, row by row, as decoded by llvmpipe. ASTC errors
+    // take the magenta of the specification instead, where llvmpipe differs.
+    type Vector = (&'static str, &'static [&'static str]);
+
+    #[test]
+    fn decodes_bc7() {
+        check(Compression::Bc7, false, BC7);
+    }
+
+    #[test]
+    fn decodes_bc4() {
+        check(Compression::Bc4, false, BC4);
+    }
+
+    #[test]
+    fn decodes_etc2() {
+        check(Compression::Etc2, false, ETC2);
+    }
+
+    #[test]
+    fn decodes_eac_r11() {
+        check(Compression::EacR11, false, EAC_R11);
+    }
+
+    #[test]
+    fn decodes_astc() {
+        use wgpu::AstcBlock;
+
+        check(Compression::Astc(AstcBlock::B4x4), false, ASTC_4X4);
+        check(Compression::Astc(AstcBlock::B5x5), false, ASTC_5X5);
+        check(Compression::Astc(AstcBlock::B6x6), false, ASTC_6X6);
+        check(Compression::Astc(AstcBlock::B8x5), false, ASTC_8X5);
+        check(Compression::Astc(AstcBlock::B8x8), false, ASTC_8X8);
+        check(Compression::Astc(AstcBlock::B4x4), true, ASTC_4X4_SRGB);
+    }
+
+    fn check(compression: Compression, is_srgb: bool, vectors: &[Vector]) {
+        let (width, height) = compression.format(is_srgb).block_dimensions();
+
+        for (block, texels) in vectors {
+            let decoded = compression.decompress(width, height, is_srgb, &hex(block));
+
+            assert_eq!(decoded, hex(&texels.concat()), "{compression:?}: {block}");
+        }
+    }
+
+    fn hex(digits: &str) -> Vec<u8> {
+        (0..digits.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    const BC7: &[Vector] = &[
+        // Mode 0
+        (
+            "cfe3a8ea0b286c7fe0abf91c871971e4",
+            &[
+                "91b6d5ff1052f7ff5f2e1dff662424ff",
+                "6e9bdfff91b6d5ff7b0839ff662424ff",
+                "5227c9ff521ce0ff6d1b2bff5f2e1dff",
+                "5233b1ff524098ff5f2e1dff662424ff",
+            ],
+        ),
+        // Mode 1
+        (
+            "dea0b858414a1735361c5bd8fa1fce62",
+            &[
+                "8533b6ff897a83ff2ed30eff476617ff",
+                "4e4b1aff897a83ff8a9072ff5a161eff",
+                "5a161eff418315ff8306d7ff8ba762ff",
+                "886393ff4e4b1aff2ed30eff864aa6ff",
+            ],
+        ),
+        // Mode 2
+        (
+            "7467b6972c9783ecc12873be3316f90c",
+            &[
+                "9c7331ffbd9439ff9c7331ffba6636ff",
+                "34a3d1ff34a3d1ff21efffff4754a1ff",
+                "21efffff5a0873ff5a0873ff34a3d1ff",
+                "beb047ffb83634ff9c7331ffb50831ff",
+            ],
+        ),
+        // Mode 3
+        (
+            "08de7d9d855e1f106d9130502597d81f",
+            &[
+                "c9f5aaff3b0361ffc9f5aaff222f4bff",
+                "7cf490ff222f4bffeff5b7ff2f1856ff",
+                "eff5b7ff164440ffa2f49dff164440ff",
+                "7cf490ff164440ffeff5b7ff3b0361ff",
+            ],
+        ),
+        // Mode 4
+        (
+            "f00b87126b4c9487d926b3c96514ca36",
+            &[
+                "690d8b91b7244ca8b724c7a8981b109f",
+                "981b109f8816c79a690dc7918816109a",
+                "981bc79f781110955a084c8ca8208ba4",
+                "981b109fa820c7a4a8208ba4690d4c91",
+            ],
+        ),
+        // Mode 5
+        (
+            "20d69aa76c34bc3479d6486eab46e834",
+            &[
+                "ad3c8d396acb0c436acb0c43ad3c8d43",
+                "6acb0c43809c3639809c362f976b6339",
+                "ad3c8d2f976b6343809c3643ad3c8d4d",
+                "6acb0c2f976b63396acb0c4d809c362f",
+            ],
+        ),
+        // Mode 6
+        (
+            "c0f94443e9312c9e26b582b52f84446f",
+            &[
+                "c0326730cc336d2fa8315b32592b3238",
+                "cc336d2f802e4635a8315b32592b3238",
+                "2628183ccc336d2fb4326131802e4635",
+                "b4326131b43261312628183c99305333",
+            ],
+        ),
+        // Mode 7
+        (
+            "cc3978afba49cb1ebf3a6f764fe2c9bf",
+            &[
+                "9b9f9affe79cadff9a64e4ff86b3e9ff",
+                "e79cadff4ca286ff7bde31ff7b64b6ff",
+                "4ca286ffe79cadff7b64b6ff7bde31ff",
+                "00a573ff00a573ff9a64e4ff9a64e4ff",
+            ],
+        ),
+        // Reserved mode
+        (
+            "00ab0ec2ae24b09bdc34f9a0942536f0",
+            &[
+                "00000000000000000000000000000000",
+                "00000000000000000000000000000000",
+                "00000000000000000000000000000000",
+                "00000000000000000000000000000000",
+            ],
+        ),
+    ];
+
+    const BC4: &[Vector] = &[
+        // Eight values
+        (
+            "b5710472349a3c45",
+            &[
+                "980000ffb50000ffb50000ff710000ff",
+                "7b0000ffb50000ff8e0000ff710000ff",
+                "ab0000ffa10000ffab0000ff840000ff",
+                "a10000ffab0000ff710000ffab0000ff",
+            ],
+        ),
+        // Six values, zero and one
+        (
+            "16b4e67dc9cb6e17",
+            &[
+                "000000ff740000ffff0000ff000000ff",
+                "ff0000ff350000ff350000ff000000ff",
+                "540000ffb40000ff540000ffff0000ff",
+                "000000ff000000ff930000ff160000ff",
+            ],
+        ),
+    ];
+
+    const ETC2: &[Vector] = &[
+        // Individual mode
+        (
+            "febcce39d84bf15807de8391630399c1",
+            &[
+                "00a14cff12ef9aff00a14cb13cffc4dd",
+                "00cb769012ef9aff00cb76b100cb76ff",
+                "89ff45ffb3ff6f9089ff45ff65dc2190",
+                "89ff4590b3ff6fddb3ff6fffb3ff6fdd",
+            ],
+        ),
+        // Differential mode
+        (
+            "7ff5814fd0de4d24554966a3169bb46f",
+            &[
+                "0200139d3a324bff6a627bf7020013f7",
+                "02001352a29ab3ff3a324bffa29ab39d",
+                "415a5a00415a5a00314a4a9d3b54549d",
+                "314a4a9d375050523b54549d415a5a9d",
+            ],
+        ),
+        // Planar mode
+        (
+            "9a625ca8263df1eedf3cf90773bf3d4f",
+            &[
+                "bebd696a92aa87a06598a47c3985c28e",
+                "c8c95ee29cb67b8e6fa399e24390b6e2",
+                "d3d4537ca6c170a07aaf8e4c4d9cabb2",
+                "dde0476ab0cd65c484ba82e257a7a0c4",
+            ],
+        ),
+        // H mode
+        (
+            "17711776b3df4b2b35fa6f8f183c3ac4",
+            &[
+                "8fe4f502ffff3a008fe4f556b4d60041",
+                "8fe4f541ffff3a003d92a36b3d92a325",
+                "b4d600563d92a3568fe4f5568fe4f541",
+                "ffff3a6b3d92a300b4d600258fe4f500",
+            ],
+        ),
+        // T mode
+        (
+            "891977ec492e1b78fab5acea51b52872",
+            &[
+                "aaccee7f93b5d790aaccee84aaccee8d",
+                "c1e3ff8d93b5d784eebb557fc1e3ff8d",
+                "aaccee92c1e3ff84eebb558aaaccee92",
+                "eebb5590aaccee84c1e3ff84eebb5587",
+            ],
+        ),
+        // Alpha with a multiplier of zero
+        (
+            "1102c9d0c364637ef5e5b4945a5610c3",
+            &[
+                "fffff711eddca9116d6d5c1105050011",
+                "c3b27f11ffffcd113d3d2c116d6d5c11",
+                "eddca911c3b27f116d6d5c113d3d2c11",
+                "ffffcd11fffff7113d3d2c116d6d5c11",
+            ],
+        ),
+    ];
+
+    const EAC_R11: &[Vector] = &[
+        // Multiplier
+        (
+            "7e51e632f192c3ad",
+            &[
+                "ba0000ff5b0000ff880000ff5b0000ff",
+                "5b0000ff3d0000ff880000ffab0000ff",
+                "880000ffab0000ff9c0000ff9c0000ff",
+                "3d0000ff5b0000ff880000ff9c0000ff",
+            ],
+        ),
+        // Multiplier of zero
+        (
+            "5c0280a7b4c9abc1",
+            &[
+                "5c0000ff5b0000ff5d0000ff5d0000ff",
+                "5c0000ff5d0000ff5b0000ff5e0000ff",
+                "5c0000ff5d0000ff5b0000ff5c0000ff",
+                "5b0000ff5c0000ff5b0000ff5c0000ff",
+            ],
+        ),
+    ];
+
+    const ASTC_4X4: &[Vector] = &[
+        // 1 partition, dual plane, endpoint mode 10
+        (
+            "9d47c1cb5d2d62bc528250ace45621f6",
+            &[
+                "5d9a744e2954403f30684f436dd5a25a",
+                "79a77e5040755946397c5e476dc89858",
+                "a0b4895356a47c5049a77e506dbb8e55",
+                "bdc193566dc1935653bb8e556dad8452",
+            ],
+        ),
+        // 1 partition, dual plane, endpoint mode 0
+        (
+            "bd05b613582eec85f6a64bc3397fcac4",
+            &[
+                "dbdbdbff934a4aff934a4affdbdbdbff",
+                "4a9393ffa03737ffa02323ff4a4a4aff",
+                "095151ff991c1cffb42323ff4a4a4aff",
+                "090909ff990909ffdb4a4affdbdbdbff",
+            ],
+        ),
+        // 2 partitions
+        (
+            "8e2b78900a07638c59f8916ee86d044e",
+            &[
+                "570d95ff6310a2ff610fa0ff824541ff",
+                "570d95ff6310a2ff6610a4ff925666ff",
+                "570d95ff610fa0ff6711a6ff6b12abff",
+                "570d95ff610fa0ff6b12abff7814b8ff",
+            ],
+        ),
+        // 1 partition, dual plane, endpoint mode 1
+        (
+            "8e25cc2e9280741f451ea3019372fa9f",
+            &[
+                "202028ff2b2b2dff303030ff303030ff",
+                "202026ff2a2a2cff2b2b2eff28282bff",
+                "202023ff27272cff28282dff202025ff",
+                "202020ff26262bff23232bff191920ff",
+            ],
+        ),
+        // 3 partitions
+        (
+            "bf51533f94222c44dc787658a80b9771",
+            &[
+                "511525ff561430ff561430ff511525ff",
+                "7cad5cff7cad5cff905282ff924e83ff",
+                "54142bff521527ff521528ff561431ff",
+                "905182ff8e5481ff8d5481ff8e5381ff",
+            ],
+        ),
+        // 2 partitions, dual plane
+        (
+            "124c13e156a805360a2e943d9ca93157",
+            &[
+                "3c3c55362323551c23236e1c3c3c3c36",
+                "2c2c3d2532324d2c23236e1c3c3c2c36",
+                "191921124545444023236e1c202023e4",
+                "0a0a0a0255553c5022221be8202025e4",
+            ],
+        ),
+        // 1 partition, endpoint mode 1
+        (
+            "ce2320726e9040b70950a782fc812b2d",
+            &[
+                "131313ff252525ff252525ff131313ff",
+                "363636ff151515ff101010ff232323ff",
+                "3d3d3dff1d1d1dff0c0c0cff070707ff",
+                "131313ff151515ff212121ff393939ff",
+            ],
+        ),
+        // 3 partitions, dual plane
+        (
+            "1ff535940e67731e3539a94865e5b153",
+            &[
+                "1c2439b2283951aa385470a144698899",
+                "173e2fb5204840af2c5c5aa735656ba2",
+                "22e95e9e186031b41d5c3bb1236047ad",
+                "284416aa0f6e20ba126525b823e760a0",
+            ],
+        ),
+        // 1 partition, dual plane, endpoint mode 12
+        (
+            "bf8595b50e30d2d97421c03f21bb0e1c",
+            &[
+                "7ec250567e5850568436844a9153f530",
+                "8bb4c23c8753a04387479d448890a343",
+                "91b4f5308a53b53f8640964685838e48",
+                "91c2f5308458844a82286f4f8928b040",
+            ],
+        ),
+        // 1 partition, dual plane, endpoint mode 8
+        (
+            "2f05adaa325734f1e5f4d62d5a2ee237",
+            &[
+                "8a94d4ff8495c8ff7e97b8ff7898acff",
+                "9894d4ff9595cfff9495ccff9295c8ff",
+                "9d95c8ff9e95cfff9f94d2ffa094daff",
+                "9d98acffa096bdffa394d4ffa693e7ff",
+            ],
+        ),
+        // 1 partition, dual plane, endpoint mode 13
+        (
+            "11a4d97e5319df9d1933fd3cfb29c246",
+            &[
+                "d5dadec6b6d4f7c6d5dadec6b6d4f7d2",
+                "d5dadec6b6d4f7c8d0d9e2c6bbd5f4cf",
+                "d5dadec6b6d4f7cacbd8e7c6c1d6efca",
+                "d5dadec6b6d4f7ccc6d7ebc6c6d7ebc6",
+            ],
+        ),
+        // 4 partitions
+        (
+            "8e7b2fe887aa29a5f906a20e8da303db",
+            &[
+                "989898189d9d9d1b919191146e6e6e00",
+                "9a9a9a1998989818909090137c7c7c08",
+                "9b9b9b19878787508a8a8a108e8e8e12",
+                "9c9c9c1a9999994c8a8a8a109c9c9c1a",
+            ],
+        ),
+        // Void extent
+        (
+            "fcfdffffffffffff1099a84e60f27a22",
+            &[
+                "994ef222994ef222994ef222994ef222",
+                "994ef222994ef222994ef222994ef222",
+                "994ef222994ef222994ef222994ef222",
+                "994ef222994ef222994ef222994ef222",
+            ],
+        ),
+        // Reserved block mode, with the error color
+        (
+            "00000000000000000000000000000000",
+            &[
+                "ff00ffffff00ffffff00ffffff00ffff",
+                "ff00ffffff00ffffff00ffffff00ffff",
+                "ff00ffffff00ffffff00ffffff00ffff",
+                "ff00ffffff00ffffff00ffffff00ffff",
+            ],
+        ),
+        // Void extent with high dynamic range, with the error color
+        (
+            "fcffffffffffffff0000000000000000",
+            &[
+                "ff00ffffff00ffffff00ffffff00ffff",
+                "ff00ffffff00ffffff00ffffff00ffff",
+                "ff00ffffff00ffffff00ffffff00ffff",
+                "ff00ffffff00ffffff00ffffff00ffff",
+            ],
+        ),
+    ];
+
+    const ASTC_5X5: &[Vector] = &[
+        // 1 partition, endpoint mode 8
+        (
+            "cd03c967e74a4d438ac22bfe2d7361e1",
+            &[
+                "a77f7aff89714fff6a6321ff8e7355ffb18389ff",
+                "be899cff987763ff71662bff8e7355ffaa807fff",
+                "987763ff88704cff7a6a38ff8b7251ff9d7a6cff",
+                "71662bff7b6b3aff86704aff8e7355ff95765fff",
+                "88704cff93765dff9d7a6cff9d7a6cff9d7a6cff",
+            ],
+        ),
+        // 3 partitions
+        (
+            "c1721868b137342b64d244a69e9b9791",
+            &[
+                "afafaf70ccccccff9f9f9f1cdddddd54e9e9e96c",
+                "bcbcbcafa6a6a6409b9b9b08e6e6e666f2f2f27e",
+                "adadad649b9b9b0cafafaf70bbbbbba7e9e9e96c",
+                "a4a4a4389d9d9d14bcbcbcafc7c7c7e3dbdbdb51",
+                "c7c7c7e3999999009f9f9f1cc7c7c7e3c1c1c1c7",
+            ],
+        ),
+    ];
+
+    const ASTC_6X6: &[Vector] = &[
+        // 1 partition, dual plane, endpoint mode 4
+        (
+            "9286085e0cf5e6181f737932be58aa27",
+            &[
+                "42424cba46464aa3474745a0494942984d4d428450504274",
+                "45454bab474749a04848449d494943954c4c458850504474",
+                "48484b9b464648a44848449a494944944a4a489150504774",
+                "4c4c4a88464647a3494944974949469449494c9850504b74",
+                "4f4f4a78464646a5494944944a4a489247474fa050504d74",
+                "5252496a464645a44a4a43904a4a4990464652a550505074",
+            ],
+        ),
+        // 4 partitions
+        (
+            "52d9006cc9a4956f60cc047dd25b0dfa",
+            &[
+                "730000ff730000ff990000ff000000ff000000ff730000ff",
+                "5b0000ff430000ff810000ff300000ff480000ff8b0000ff",
+                "799797ff293434ff99bfbfff799797ffa3cbcbffccffffff",
+                "b9e7e7ff090c0cff99bfbfffb9e7e7ffc3f3f3ffccffffff",
+                "8d00bcff7c00a6ff8e00beff9000c0ff9600c8ff8d00bcff",
+                "7900a2ff8100adff8900b7ff8100adff9100c2ff7900a2ff",
+            ],
+        ),
+    ];
+
+    const ASTC_8X5: &[Vector] = &[
+        // 1 partition, dual plane, endpoint mode 13
+        (
+            "03a509bc8c9596fc8996aabe786b68b6",
+            &[
+                "3cac73093dab6e083dac71083bac78083bac78083bac78083cac75083eab6c08",
+                "3cac73093dac70083dac71083bac77083bac78083bac78083cac76083dab6f08",
+                "3cac73093dac71083cac72083cac76083bad79083bad79083bac78083dac7209",
+                "3cac73093cac72083cac73083cac75083bad7a083bad7b083bad79083cac7509",
+                "3cac73093cac73083cac73083cac74083bad7b083aad7b083bad7b083bac780a",
+            ],
+        ),
+        // 3 partitions
+        (
+            "d2d0696cf5cced0af377394904ca60bd",
+            &[
+                "8ebda6ff99ccb2ff9aceb4ff5f7f97ff785f67ff785f67ff967681ffbc94a1ff",
+                "628373ff87b49dff9aceb4ff5f7f97ff7e636cff886b74ff92737dff9a7984ff",
+                "547062ff678a78ff79a28eff5b7a91ff496274ff7a6068ff7e636cff846871ff",
+                "5d7d6dff5d7d6dff5d7d6dff455d6eff496274ff496274ff80646dff896c76ff",
+                "7aa48fff7aa48fff769d89ff354754ff496274ff91c2e6ffbc94a1ffbc94a1ff",
+            ],
+        ),
+    ];
+
+    const ASTC_8X8: &[Vector] = &[
+        // 1 partition, dual plane, endpoint mode 9
+        (
+            "6e275503ca716a62a096b7cb2c5a8c03",
+            &[
+                "a4509affa44f9affa44d9affa44c9affa44a9affa4499bffa4479bffa4469bff",
+                "a24c9fffa24b9effa24a9effa2499dffa3489dffa3489dffa3479dffa3469cff",
+                "a049a1ffa149a1ffa149a1ffa148a0ffa148a0ffa2489fffa2489fffa2479eff",
+                "a24e9effa24e9effa24e9fffa14ea0ffa14ea0ffa04ea1ffa04ea2ffa04ea2ff",
+                "a24e9effa24e9effa24e9fffa14ea0ffa14ea1ffa04ea1ffa04ea2ffa04da3ff",
+                "a14aa1ffa149a0ffa149a1ffa148a0ffa147a0ffa2469fffa2469fffa2459fff",
+                "a148a0ffa148a0ffa147a0ffa2479fffa2479fffa2469fffa2469fffa2459eff",
+                "a2479fffa2479fffa2479fffa2489fffa2489fffa2489fffa2489fffa2489fff",
+            ],
+        ),
+        // 1 partition, endpoint mode 6
+        (
+            "3ec2cef4641281ff3a10a4f644babff3",
+            &[
+                "536328ff505f26ff4e5c25ff4c5a24ff4a5823ff4a5823ff4a5823ff4a5823ff",
+                "536328ff536328ff536328ff546428ff556529ff57672aff596a2bff5b6c2cff",
+                "495623ff4c5a24ff4e5c25ff516027ff546428ff59692bff5d6e2dff62742fff",
+                "3d481dff414c1eff445120ff475422ff4c5a24ff516027ff566629ff5c6d2cff",
+                "475422ff465321ff455221ff445120ff434f20ff424e1fff414c1eff404b1eff",
+                "3e4a1dff414d1fff434f20ff465321ff475422ff455221ff445020ff434f20ff",
+                "3d491dff424e1fff465321ff4a5723ff4b5924ff4a5723ff485522ff475422ff",
+                "516027ff516027ff516027ff516027ff4f5d26ff4a5723ff465321ff414c1eff",
+            ],
+        ),
+    ];
+
+    const ASTC_4X4_SRGB: &[Vector] = &[
+        // sRGB
+        (
+            "4e87c3fb0e1d9892c013bc418a7dfff3",
+            &[
+                "c3ae77eac3ae81eac3ae8aeac3ae93ea",
+                "bdaa6ce7a59a5adc878743cd6d7630c1",
+                "737a50c4888840cea2982cdab8a71ce5",
+                "999377d68f8c53d1828425cb787d00c6",
+            ],
+        ),
+    ];
+}
@@ -0,0 +1,768 @@
+// ASTC block decoding, limited to 2D blocks with low dynamic range
+//
+// Texels of blocks that fail to decode take the error color.
+
+const ERROR: [u8; 4] = [255, 0, 255, 255];
+
+pub fn decode(block: &[u8; 16], width: usize, height: usize, srgb: bool, texels: &mut [[u8; 4]]) {
+    let texels = &mut texels[..width * height];
+
+    match decode_block(u128::from_le_bytes(*block), width, height, srgb, texels) {
+        Some(()) => {}
+        None => texels.fill(ERROR),
+    }
+}
+
+fn decode_block(
+    block: u128,
+    width: usize,
+    height: usize,
+    srgb: bool,
+    texels: &mut [[u8; 4]],
+) -> Option<()> {
+    let bits = |offset: usize, count: usize| (block >> offset) as u32 & ((1 << count) - 1);
+
+    // Void extent, a single color for the whole block
+    if bits(0, 9) == 0x1fc {
+        // HDR or reserved
+        if bits(9, 1) == 1 || bits(10, 2) != 0x3 {
+            return None;
+        }
+
+        let extent = [bits(12, 13), bits(25, 13), bits(38, 13), bits(51, 13)];
+
+        if extent != [0x1fff; 4] && (extent[0] >= extent[1] || extent[2] >= extent[3]) {
+            return None;
+        }
+
+        let color = [bits(64, 16), bits(80, 16), bits(96, 16), bits(112, 16)]
+            .map(|channel| (channel >> 8) as u8);
+
+        texels.fill(color);
+
+        return Some(());
+    }
+
+    let layout = Layout::decode(bits(0, 11))?;
+
+    if layout.grid_width > width || layout.grid_height > height {
+        return None;
+    }
+
+    let partitions = bits(11, 2) as usize + 1;
+
+    if partitions == 4 && layout.is_dual_plane {
+        return None;
+    }
+
+    let planes = if layout.is_dual_plane { 2 } else { 1 };
+    let weight_count = layout.grid_width * layout.grid_height * planes;
+    let weight_bits = layout.weights.bit_count(weight_count);
+
+    if weight_count > 64 || !(24..=96).contains(&weight_bits) {
+        return None;
+    }
+
+    let mut below_weights = 128 - weight_bits;
+    let mut modes = [0; 4];
+
+    let colors_start = if partitions == 1 {
+        modes[0] = bits(13, 4);
+
+        17
+    } else {
+        let encoding = bits(23, 6);
+        let class = encoding & 0x3;
+
+        if class == 0 {
+            modes[..partitions].fill(encoding >> 2);
+        } else {
+            let extra = 3 * partitions - 4;
+            below_weights -= extra;
+
+            let encoding = encoding >> 2 | bits(below_weights, extra) << 4;
+
+            for (partition, mode) in modes[..partitions].iter_mut().enumerate() {
+                let offset = encoding >> partition & 1;
+                let low = encoding >> (partitions + 2 * partition) & 0x3;
+
+                *mode = (class - 1 + offset) << 2 | low;
+            }
+        }
+
+        29
+    };
+
+    let plane_component = if layout.is_dual_plane {
+        below_weights -= 2;
+
+        Some(bits(below_weights, 2) as usize)
+    } else {
+        None
+    };
+
+    let color_count: usize = modes[..partitions]
+        .iter()
+        .map(|mode| 2 * (*mode as usize >> 2) + 2)
+        .sum();
+
+    if color_count > 18 || below_weights < colors_start {
+        return None;
+    }
+
+    let color_bits = below_weights - colors_start;
+
+    let color_range = RANGES
+        .iter()
+        .rev()
+        .find(|range| range.bit_count(color_count) <= color_bits)?;
+
+    // Endpoints need at least 6 levels
+    if color_range.levels < 6 {
+        return None;
+    }
+
+    let colors = color_range.decode(block >> colors_start, color_count);
+    let colors: Vec<u32> = colors
+        .into_iter()
+        .map(|value| color_range.unquantize_color(value))
+        .collect();
+
+    let mut endpoints = [[[0u32; 4]; 2]; 4];
+    let mut values = colors.as_slice();
+
+    for (mode, endpoint) in modes[..partitions].iter().zip(&mut endpoints) {
+        let count = 2 * (*mode as usize >> 2) + 2;
+
+        *endpoint = decode_endpoints(*mode, &values[..count])?;
+        values = &values[count..];
+    }
+
+    // Weights are stored backwards from the end of the block
+    let weights: Vec<u32> = layout
+        .weights
+        .decode(block.reverse_bits(), weight_count)
+        .into_iter()
+        .map(|value| layout.weights.unquantize_weight(value))
+        .collect();
+
+    let seed = bits(13, 10);
+    let is_small = width * height < 31;
+
+    for (texel, output) in texels.iter_mut().enumerate() {
+        let (x, y) = (texel % width, texel / width);
+
+        let partition = if partitions > 1 {
+            select_partition(seed, x as u32, y as u32, partitions as u32, is_small)
+        } else {
+            0
+        };
+
+        let [low, high] = endpoints[partition];
+
+        let weight = |plane: usize| {
+            infill(
+                &weights,
+                planes,
+                plane,
+                (x, y),
+                (width, height),
+                (layout.grid_width, layout.grid_height),
+            )
+        };
+
+        let primary = weight(0);
+        let secondary = plane_component.map(|_| weight(1));
+
+        for channel in 0..4 {
+            let weight = match (plane_component, secondary) {
+                (Some(component), Some(secondary)) if component == channel => secondary,
+                _ => primary,
+            };
+
+            let expand = |value: u32| {
+                if srgb {
+                    value << 8 | 0x80
+                } else {
+                    value << 8 | value
+                }
+            };
+
+            let value =
+                (expand(low[channel]) * (64 - weight) + expand(high[channel]) * weight + 32) >> 6;
+
+            output[channel] = (value >> 8) as u8;
+        }
+    }
+
+    Some(())
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Layout {
+    grid_width: usize,
+    grid_height: usize,
+    is_dual_plane: bool,
+    weights: Range,
+}
+
+impl Layout {
+    fn decode(mode: u32) -> Option<Self> {
+        let bit = |offset: u32| mode >> offset & 1;
+        let a = (mode >> 5 & 0x3) as usize;
+
+        let mut high_precision = bit(9) == 1;
+        let mut is_dual_plane = bit(10) == 1;
+        let mut range = bit(4);
+
+        let (grid_width, grid_height) = if mode & 0x3 != 0 {
+            range |= (mode & 0x3) << 1;
+            let b = (mode >> 7 & 0x3) as usize;
+
+            match mode >> 2 & 0x3 {
+                0 => (b + 4, a + 2),
+                1 => (b + 8, a + 2),
+                2 => (a + 2, b + 8),
+                _ if bit(8) == 1 => ((b & 1) + 2, a + 2),
+                _ => (a + 2, (b & 1) + 6),
+            }
+        } else {
+            range |= (mode >> 2 & 0x3) << 1;
+
+            if mode >> 2 & 0x3 == 0 {
+                return None;
+            }
+
+            let b = (mode >> 9 & 0x3) as usize;
+
+            match mode >> 7 & 0x3 {
+                0 => (12, a + 2),
+                1 => (a + 2, 12),
+                2 => {
+                    high_precision = false;
+                    is_dual_plane = false;
+
+                    (a + 6, b + 6)
+                }
+                _ => match a {
+                    0 => (6, 10),
+                    1 => (10, 6),
+                    _ => return None,
+                },
+            }
+        };
+
+        let weights = RANGES[(range as usize - 2) + if high_precision { 6 } else { 0 }];
+
+        Some(Self {
+            grid_width,
+            grid_height,
+            is_dual_plane,
+            weights,
+        })
+    }
+}
+
+// An integer sequence encoding range, made of bits and an optional trit or quint
+#[derive(Debug, Clone, Copy)]
+struct Range {
+    levels: u32,
+    bits: usize,
+    trits: bool,
+    quints: bool,
+}
+
+const fn range(levels: u32, bits: usize, trits: bool, quints: bool) -> Range {
+    Range {
+        levels,
+        bits,
+        trits,
+        quints,
+    }
+}
+
+const RANGES: [Range; 21] = [
+    range(2, 1, false, false),
+    range(3, 0, true, false),
+    range(4, 2, false, false),
+    range(5, 0, false, true),
+    range(6, 1, true, false),
+    range(8, 3, false, false),
+    range(10, 1, false, true),
+    range(12, 2, true, false),
+    range(16, 4, false, false),
+    range(20, 2, false, true),
+    range(24, 3, true, false),
+    range(32, 5, false, false),
+    range(40, 3, false, true),
+    range(48, 4, true, false),
+    range(64, 6, false, false),
+    range(80, 4, false, true),
+    range(96, 5, true, false),
+    range(128, 7, false, false),
+    range(160, 5, false, true),
+    range(192, 6, true, false),
+    range(256, 8, false, false),
+];
+
+impl Range {
+    fn bit_count(self, count: usize) -> usize {
+        let extra = if self.trits {
+            (8 * count).div_ceil(5)
+        } else if self.quints {
+            (7 * count).div_ceil(3)
+        } else {
+            0
+        };
+
+        count * self.bits + extra
+    }
+
+    fn decode(self, data: u128, count: usize) -> Vec<u32> {
+        let end = self.bit_count(count);
+        let mut reader = Reader {
+            data,
+            position: 0,
+            end,
+        };
+
+        let mut values = Vec::with_capacity(count + 4);
+        let bits = self.bits;
+
+        while values.len() < count {
+            if self.trits {
+                let mut low = [0; 5];
+                let mut packed = 0;
+
+                for (i, (size, shift)) in [(2, 0), (2, 2), (1, 4), (2, 5), (1, 7)]
+                    .into_iter()
+                    .enumerate()
+                {
+                    low[i] = reader.read(bits);
+                    packed |= reader.read(size) << shift;
+                }
+
+                for (low, trit) in low.into_iter().zip(trits(packed)) {
+                    values.push(trit << bits | low);
+                }
+            } else if self.quints {
+                let mut low = [0; 3];
+                let mut packed = 0;
+
+                for (i, (size, shift)) in [(3, 0), (2, 3), (2, 5)].into_iter().enumerate() {
+                    low[i] = reader.read(bits);
+                    packed |= reader.read(size) << shift;
+                }
+
+                for (low, quint) in low.into_iter().zip(quints(packed)) {
+                    values.push(quint << bits | low);
+                }
+            } else {
+                values.push(reader.read(bits));
+            }
+        }
+
+        values.truncate(count);
+        values
+    }
+
+    fn unquantize_color(self, value: u32) -> u32 {
+        let bits = self.bits as u32;
+
+        if !self.trits && !self.quints {
+            return replicate(value, bits, 8);
+        }
+
+        let a = if value & 1 == 1 { 0x1ff } else { 0 };
+        let b = value >> 1;
+        let bit = |n: u32| b >> n & 1;
+        let d = value >> bits;
+
+        let (b, c) = if self.trits {
+            match bits {
+                1 => (0, 204),
+                2 => (bit(0) * 0b100010110, 93),
+                3 => {
+                    let cb = b & 0x3;
+                    (cb << 7 | cb << 2 | cb, 44)
+                }
+                4 => {
+                    let dcb = b & 0x7;
+                    (dcb << 6 | dcb, 22)
+                }
+                5 => {
+                    let edcb = b & 0xf;
+                    (edcb << 5 | edcb >> 2, 11)
+                }
+                _ => {
+                    let fedcb = b & 0x1f;
+                    (fedcb << 4 | fedcb >> 4, 5)
+                }
+            }
+        } else {
+            match bits {
+                1 => (0, 113),
+                2 => (bit(0) * 0b100001100, 54),
+                3 => {
+                    let cb = b & 0x3;
+                    (cb << 7 | cb << 1 | cb >> 1, 26)
+                }
+                4 => {
+                    let dcb = b & 0x7;
+                    (dcb << 6 | dcb >> 1, 13)
+                }
+                _ => {
+                    let edcb = b & 0xf;
+                    (edcb << 5 | edcb >> 3, 6)
+                }
+            }
+        };
+
+        let t = (d * c + b) ^ a;
+
+        (a & 0x80) | t >> 2
+    }
+
+    fn unquantize_weight(self, value: u32) -> u32 {
+        let bits = self.bits as u32;
+
+        let weight = if !self.trits && !self.quints {
+            replicate(value, bits, 6)
+        } else if bits == 0 {
+            if self.trits {
+                [0, 32, 63][value as usize]
+            } else {
+                [0, 16, 32, 47, 63][value as usize]
+            }
+        } else {
+            let a = if value & 1 == 1 { 0x7f } else { 0 };
+            let b = value >> 1;
+            let bit = |n: u32| b >> n & 1;
+            let d = value >> bits;
+
+            let (b, c) = if self.trits {
+                match bits {
+                    1 => (0, 50),
+                    2 => (bit(0) * 0b1000101, 23),
+                    _ => {
+                        let cb = b & 0x3;
+                        (cb << 5 | cb, 11)
+                    }
+                }
+            } else {
+                match bits {
+                    1 => (0, 28),
+                    _ => (bit(0) * 0b1000010, 13),
+                }
+            };
+
+            let t = (d * c + b) ^ a;
+
+            (a & 0x20) | t >> 2
+        };
+
+        if weight > 32 { weight + 1 } else { weight }
+    }
+}
+
+struct Reader {
+    data: u128,
+    position: usize,
+    end: usize,
+}
+
+impl Reader {
+    // Bits past the end of the sequence read as zeros
+    fn read(&mut self, count: usize) -> u32 {
+        let available = self.end.saturating_sub(self.position).min(count);
+        let value = if available == 0 {
+            0
+        } else {
+            (self.data >> self.position) as u32 & ((1 << available) - 1)
+        };
+
+        self.position += count;
+
+        value
+    }
+}
+
+fn trits(packed: u32) -> [u32; 5] {
+    let bit = |n: u32| packed >> n & 1;
+    let bits = |high: u32, low: u32| packed >> low & ((1 << (high - low + 1)) - 1);
+
+    let (c, t4, t3) = if bits(4, 2) == 0b111 {
+        (bits(7, 5) << 2 | bits(1, 0), 2, 2)
+    } else {
+        let c = bits(4, 0);
+
+        if bits(6, 5) == 0b11 {
+            (c, 2, bit(7))
+        } else {
+            (c, bit(7), bits(6, 5))
+        }
+    };
+
+    let c_bit = |n: u32| c >> n & 1;
+
+    let (t2, t1, t0) = if c & 0x3 == 0x3 {
+        (2, c_bit(4), c_bit(3) << 1 | (c_bit(2) & !c_bit(3) & 1))
+    } else if c >> 2 & 0x3 == 0x3 {
+        (2, 2, c & 0x3)
+    } else {
+        (
+            c_bit(4),
+            c >> 2 & 0x3,
+            c_bit(1) << 1 | (c_bit(0) & !c_bit(1) & 1),
+        )
+    };
+
+    [t0, t1, t2, t3, t4]
+}
+
+fn quints(packed: u32) -> [u32; 3] {
+    let bit = |n: u32| packed >> n & 1;
+    let bits = |high: u32, low: u32| packed >> low & ((1 << (high - low + 1)) - 1);
+
+    if bits(2, 1) == 0b11 && bits(6, 5) == 0 {
+        let q2 = bit(0) << 2 | (bit(4) & !bit(0) & 1) << 1 | (bit(3) & !bit(0) & 1);
+
+        return [4, 4, q2];
+    }
+
+    let (q2, c) = if bits(2, 1) == 0b11 {
+        (4, bits(4, 3) << 3 | (!bits(6, 5) & 0x3) << 1 | bit(0))
+    } else {
+        (bits(6, 5), bits(4, 0))
+    };
+
+    let (q1, q0) = if c & 0x7 == 0b101 {
+        (4, c >> 3 & 0x3)
+    } else {
+        (c >> 3 & 0x3, c & 0x7)
+    };
+
+    [q0, q1, q2]
+}
+
+fn replicate(value: u32, bits: u32, target: u32) -> u32 {
+    if bits == 0 {
+        return 0;
+    }
+
+    let mut result = 0;
+    let mut filled = 0;
+
+    while filled < target {
+        result = result << bits | value;
+        filled += bits;
+    }
+
+    result >> (filled - target)
+}
+
+fn decode_endpoints(mode: u32, v: &[u32]) -> Option<[[u32; 4]; 2]> {
+    let v: Vec<i32> = v.iter().map(|value| *value as i32).collect();
+    let clamp = |color: [i32; 4]| color.map(|channel| channel.clamp(0, 255) as u32);
+
+    let blue_contract = |[r, g, b, a]: [i32; 4]| [(r + b) >> 1, (g + b) >> 1, b, a];
+
+    let transfer = |a: i32, b: i32| {
+        let b = (b >> 1) | (a & 0x80);
+        let a = (a >> 1) & 0x3f;
+        let a = if a & 0x20 != 0 { a - 0x40 } else { a };
+
+        (a, b)
+    };
+
+    let [low, high] = match mode {
+        0 => [[v[0], v[0], v[0], 255], [v[1], v[1], v[1], 255]],
+        1 => {
+            let l0 = (v[0] >> 2) | (v[1] & 0xc0);
+            let l1 = (l0 + (v[1] & 0x3f)).min(255);
+
+            [[l0, l0, l0, 255], [l1, l1, l1, 255]]
+        }
+        4 => [[v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]],
+        5 => {
+            let (d0, b0) = transfer(v[1], v[0]);
+            let (d1, b1) = transfer(v[3], v[2]);
+
+            [[b0, b0, b0, b1], [b0 + d0, b0 + d0, b0 + d0, b1 + d1]]
+        }
+        6 => [
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                255,
+            ],
+            [v[0], v[1], v[2], 255],
+        ],
+        8 | 12 => {
+            let (a0, a1) = if mode == 12 { (v[6], v[7]) } else { (255, 255) };
+
+            if v[1] + v[3] + v[5] >= v[0] + v[2] + v[4] {
+                [[v[0], v[2], v[4], a0], [v[1], v[3], v[5], a1]]
+            } else {
+                [
+                    blue_contract([v[1], v[3], v[5], a1]),
+                    blue_contract([v[0], v[2], v[4], a0]),
+                ]
+            }
+        }
+        9 | 13 => {
+            let (d0, b0) = transfer(v[1], v[0]);
+            let (d1, b1) = transfer(v[3], v[2]);
+            let (d2, b2) = transfer(v[5], v[4]);
+
+            let (d3, b3) = if mode == 13 {
+                transfer(v[7], v[6])
+            } else {
+                (0, 255)
+            };
+
+            if d0 + d1 + d2 >= 0 {
+                [[b0, b1, b2, b3], [b0 + d0, b1 + d1, b2 + d2, b3 + d3]]
+            } else {
+                [
+                    blue_contract([b0 + d0, b1 + d1, b2 + d2, b3 + d3]),
+                    blue_contract([b0, b1, b2, b3]),
+                ]
+            }
+        }
+        10 => [
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                v[4],
+            ],
+            [v[0], v[1], v[2], v[5]],
+        ],
+        // HDR
+        _ => return None,
+    };
+
+    Some([clamp(low), clamp(high)])
+}
+
+// Bilinear infill of the weight grid, in fixed point
+fn infill(
+    weights: &[u32],
+    planes: usize,
+    plane: usize,
+    (x, y): (usize, usize),
+    (width, height): (usize, usize),
+    (grid_width, grid_height): (usize, usize),
+) -> u32 {
+    let scale = |size: usize| (1024 + size / 2) / (size - 1).max(1);
+
+    let s = (scale(width) * x * (grid_width - 1) + 32) >> 6;
+    let t = (scale(height) * y * (grid_height - 1) + 32) >> 6;
+
+    let (js, fs) = (s >> 4, s & 0xf);
+    let (jt, ft) = (t >> 4, t & 0xf);
+
+    let weight = |i: usize, j: usize| {
+        weights
+            .get(((jt + j) * grid_width + js + i) * planes + plane)
+            .copied()
+            .unwrap_or(0)
+    };
+
+    let w11 = (fs * ft + 8) >> 4;
+    let w10 = ft - w11;
+    let w01 = fs - w11;
+    let w00 = 16 + w11 - fs - ft;
+
+    ((weight(0, 0) as usize * w00
+        + weight(1, 0) as usize * w01
+        + weight(0, 1) as usize * w10
+        + weight(1, 1) as usize * w11
+        + 8)
+        >> 4) as u32
+}
+
+fn select_partition(seed: u32, x: u32, y: u32, partitions: u32, is_small: bool) -> usize {
+    let (x, y) = if is_small { (x << 1, y << 1) } else { (x, y) };
+
+    let seed = seed + (partitions - 1) * 1024;
+    let random = hash(seed);
+
+    let mut seeds = [0u32; 12];
+
+    for (i, shift) in [0, 4, 8, 12, 16, 20, 24, 28, 18, 22, 26]
+        .into_iter()
+        .enumerate()
+    {
+        seeds[i] = random >> shift & 0xf;
+    }
+
+    seeds[11] = random.rotate_left(2) & 0xf;
+
+    for seed in &mut seeds {
+        *seed *= *seed;
+    }
+
+    let (sh1, sh2) = if seed & 1 != 0 {
+        (
+            if seed & 2 != 0 { 4 } else { 5 },
+            if partitions == 3 { 6 } else { 5 },
+        )
+    } else {
+        (
+            if partitions == 3 { 6 } else { 5 },
+            if seed & 2 != 0 { 4 } else { 5 },
+        )
+    };
+
+    let sh3 = if seed & 0x10 != 0 { sh1 } else { sh2 };
+
+    for (i, seed) in seeds.iter_mut().enumerate() {
+        *seed >>= match i {
+            0..8 if i % 2 == 0 => sh1,
+            0..8 => sh2,
+            _ => sh3,
+        };
+    }
+
+    // Blocks are flat, so the z coordinate is always zero
+    let a = (seeds[0] * x + seeds[1] * y + (random >> 14)) & 0x3f;
+    let b = (seeds[2] * x + seeds[3] * y + (random >> 10)) & 0x3f;
+    let c = if partitions < 3 {
+        0
+    } else {
+        (seeds[4] * x + seeds[5] * y + (random >> 6)) & 0x3f
+    };
+    let d = if partitions < 4 {
+        0
+    } else {
+        (seeds[6] * x + seeds[7] * y + (random >> 2)) & 0x3f
+    };
+
+    if a >= b && a >= c && a >= d {
+        0
+    } else if b >= c && b >= d {
+        1
+    } else if c >= d {
+        2
+    } else {
+        3
+    }
+}
+
+fn hash(mut p: u32) -> u32 {
+    p ^= p >> 15;
+    p = p.wrapping_sub(p << 17);
+    p = p.wrapping_add(p << 7);
+    p = p.wrapping_add(p << 4);
+    p ^= p >> 5;
+    p = p.wrapping_add(p << 16);
+    p ^= p >> 7;
+    p ^= p >> 3;
+    p ^= p << 6;
+    p ^= p >> 17;
+
+    p
+}
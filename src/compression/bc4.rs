@@ -0,0 +1,27 @@
+// BC4 block decoding: two endpoints and 3-bit indices for a single channel
+
+pub fn decode(block: &[u8; 8], texels: &mut [[u8; 4]]) {
+    let block = u64::from_le_bytes(*block);
+    let (low, high) = (block as i32 & 0xff, (block >> 8) as i32 & 0xff);
+
+    // Values are interpolated with 8-bit weights, like llvmpipe does
+    let lerp = |step: i32, steps: i32| low + (((high - low) * (step * 255 / steps)) >> 8);
+
+    let mut palette = [low, high, 0, 0, 0, 0, 0, 255];
+
+    if low > high {
+        for (step, value) in (1..).zip(&mut palette[2..]) {
+            *value = lerp(step, 7);
+        }
+    } else {
+        for (step, value) in (1..).zip(&mut palette[2..6]) {
+            *value = lerp(step, 5);
+        }
+    }
+
+    for (texel, pixel) in texels.iter_mut().enumerate().take(16) {
+        let index = (block >> (16 + 3 * texel) & 0x7) as usize;
+
+        *pixel = [palette[index] as u8, 0, 0, 255];
+    }
+}
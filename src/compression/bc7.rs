@@ -0,0 +1,386 @@
+// BC7 block decoding, following the BPTC specification
+
+pub fn decode(block: &[u8; 16], texels: &mut [[u8; 4]]) {
+    let mode = block[0].trailing_zeros() as usize;
+
+    // Reserved mode
+    if mode >= MODES.len() {
+        texels.fill([0; 4]);
+        return;
+    }
+
+    let Mode {
+        subsets,
+        partition_bits,
+        rotation_bits,
+        selection_bits,
+        color_bits,
+        alpha_bits,
+        endpoint_pbits,
+        shared_pbits,
+        index_bits,
+        secondary_index_bits,
+    } = MODES[mode];
+
+    let mut bits = Bits(u128::from_le_bytes(*block));
+    let _ = bits.read(mode as u32 + 1);
+
+    let partition = bits.read(partition_bits) as usize;
+    let rotation = bits.read(rotation_bits);
+    let selection = bits.read(selection_bits);
+
+    let mut endpoints = [[0u32; 4]; 6];
+    let endpoints = &mut endpoints[..2 * subsets];
+
+    for channel in 0..3 {
+        for endpoint in endpoints.iter_mut() {
+            endpoint[channel] = bits.read(color_bits);
+        }
+    }
+
+    for endpoint in endpoints.iter_mut() {
+        endpoint[3] = bits.read(alpha_bits);
+    }
+
+    if endpoint_pbits {
+        for endpoint in endpoints.iter_mut() {
+            let pbit = bits.read(1);
+            endpoint
+                .iter_mut()
+                .for_each(|value| *value = *value << 1 | pbit);
+        }
+    }
+
+    if shared_pbits {
+        for pair in endpoints.chunks_exact_mut(2) {
+            let pbit = bits.read(1);
+
+            for endpoint in pair {
+                endpoint
+                    .iter_mut()
+                    .for_each(|value| *value = *value << 1 | pbit);
+            }
+        }
+    }
+
+    let pbit = u32::from(endpoint_pbits || shared_pbits);
+
+    let endpoints: Vec<[u8; 4]> = endpoints
+        .iter()
+        .map(|endpoint| {
+            [
+                expand(endpoint[0], color_bits + pbit),
+                expand(endpoint[1], color_bits + pbit),
+                expand(endpoint[2], color_bits + pbit),
+                if alpha_bits == 0 {
+                    255
+                } else {
+                    expand(endpoint[3], alpha_bits + pbit)
+                },
+            ]
+        })
+        .collect();
+
+    let subset = |texel: usize| match subsets {
+        2 => (PARTITIONS_2[partition] >> texel) as usize & 1,
+        3 => PARTITIONS_3[partition][texel] as usize,
+        _ => 0,
+    };
+
+    let is_anchor = |texel: usize| {
+        texel == 0
+            || (subsets == 2 && texel == ANCHORS_2[partition] as usize)
+            || (subsets == 3
+                && (texel == ANCHORS_3_2[partition] as usize
+                    || texel == ANCHORS_3_3[partition] as usize))
+    };
+
+    let mut primary = [0; 16];
+    let mut secondary = [0; 16];
+
+    for (texel, index) in primary.iter_mut().enumerate() {
+        *index = bits.read(index_bits - u32::from(is_anchor(texel)));
+    }
+
+    if secondary_index_bits > 0 {
+        for (texel, index) in secondary.iter_mut().enumerate() {
+            *index = bits.read(secondary_index_bits - u32::from(texel == 0));
+        }
+    }
+
+    for (texel, output) in texels.iter_mut().enumerate().take(16) {
+        let subset = subset(texel);
+        let (low, high) = (endpoints[2 * subset], endpoints[2 * subset + 1]);
+
+        let ((color, color_bits), (alpha, alpha_bits)) = if secondary_index_bits == 0 {
+            ((primary[texel], index_bits), (primary[texel], index_bits))
+        } else if selection == 0 {
+            (
+                (primary[texel], index_bits),
+                (secondary[texel], secondary_index_bits),
+            )
+        } else {
+            (
+                (secondary[texel], secondary_index_bits),
+                (primary[texel], index_bits),
+            )
+        };
+
+        let color = weight(color, color_bits);
+        let alpha = weight(alpha, alpha_bits);
+
+        let mut pixel = [
+            interpolate(low[0], high[0], color),
+            interpolate(low[1], high[1], color),
+            interpolate(low[2], high[2], color),
+            interpolate(low[3], high[3], alpha),
+        ];
+
+        match rotation {
+            1 => pixel.swap(0, 3),
+            2 => pixel.swap(1, 3),
+            3 => pixel.swap(2, 3),
+            _ => {}
+        }
+
+        *output = pixel;
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    secondary_index_bits: u32,
+}
+
+const MODES: [Mode; 8] = [
+    Mode {
+        subsets: 3,
+        partition_bits: 4,
+        rotation_bits: 0,
+        selection_bits: 0,
+        color_bits: 4,
+        alpha_bits: 0,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 3,
+        secondary_index_bits: 0,
+    },
+    Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        selection_bits: 0,
+        color_bits: 6,
+        alpha_bits: 0,
+        endpoint_pbits: false,
+        shared_pbits: true,
+        index_bits: 3,
+        secondary_index_bits: 0,
+    },
+    Mode {
+        subsets: 3,
+        partition_bits: 6,
+        rotation_bits: 0,
+        selection_bits: 0,
+        color_bits: 5,
+        alpha_bits: 0,
+        endpoint_pbits: false,
+        shared_pbits: false,
+        index_bits: 2,
+        secondary_index_bits: 0,
+    },
+    Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 0,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 2,
+        secondary_index_bits: 0,
+    },
+    Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 2,
+        selection_bits: 1,
+        color_bits: 5,
+        alpha_bits: 6,
+        endpoint_pbits: false,
+        shared_pbits: false,
+        index_bits: 2,
+        secondary_index_bits: 3,
+    },
+    Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 2,
+        selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 8,
+        endpoint_pbits: false,
+        shared_pbits: false,
+        index_bits: 2,
+        secondary_index_bits: 2,
+    },
+    Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 0,
+        selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 7,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 4,
+        secondary_index_bits: 0,
+    },
+    Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        selection_bits: 0,
+        color_bits: 5,
+        alpha_bits: 5,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 2,
+        secondary_index_bits: 0,
+    },
+];
+
+struct Bits(u128);
+
+impl Bits {
+    fn read(&mut self, n: u32) -> u32 {
+        let value = (self.0 & ((1 << n) - 1)) as u32;
+        self.0 >>= n;
+
+        value
+    }
+}
+
+fn expand(value: u32, bits: u32) -> u8 {
+    let value = value << (8 - bits);
+
+    (value | value >> bits) as u8
+}
+
+fn weight(index: u32, bits: u32) -> u32 {
+    match bits {
+        2 => [0, 21, 43, 64][index as usize],
+        3 => [0, 9, 18, 27, 37, 46, 55, 64][index as usize],
+        _ => [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64][index as usize],
+    }
+}
+
+fn interpolate(low: u8, high: u8, weight: u32) -> u8 {
+    ((u32::from(low) * (64 - weight) + u32::from(high) * weight + 32) >> 6) as u8
+}
+
+// The subset of every texel, one bit each
+const PARTITIONS_2: [u16; 64] = [
+    0xcccc, 0x8888, 0xeeee, 0xecc8, 0xc880, 0xfeec, 0xfec8, 0xec80, 0xc800, 0xffec, 0xfe80, 0xe800,
+    0xffe8, 0xff00, 0xfff0, 0xf000, 0xf710, 0x008e, 0x7100, 0x08ce, 0x008c, 0x7310, 0x3100, 0x8cce,
+    0x088c, 0x3110, 0x6666, 0x366c, 0x17e8, 0x0ff0, 0x718e, 0x399c, 0xaaaa, 0xf0f0, 0x5a5a, 0x33cc,
+    0x3c3c, 0x55aa, 0x9696, 0xa55a, 0x73ce, 0x13c8, 0x324c, 0x3bdc, 0x6996, 0xc33c, 0x9966, 0x0660,
+    0x0272, 0x04e4, 0x4e40, 0x2720, 0xc936, 0x936c, 0x39c6, 0x639c, 0x9336, 0x9cc6, 0x817e, 0xe718,
+    0xccf0, 0x0fcc, 0x7744, 0xee22,
+];
+
+const PARTITIONS_3: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 1, 2, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 2, 0, 0, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2],
+    [0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0, 2, 2, 2, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2],
+    [0, 1, 1, 1, 0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0],
+    [0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2, 0, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 0, 1, 2, 2, 2, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 1, 0, 2, 2, 1, 0],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1, 0, 0, 0, 0],
+    [0, 0, 1, 2, 0, 0, 1, 2, 1, 1, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1, 0, 1, 1, 0],
+    [0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1],
+    [0, 0, 2, 2, 1, 1, 0, 2, 1, 1, 0, 2, 0, 0, 2, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 0, 0, 2, 2, 2, 2, 2],
+    [0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 0, 0, 2, 0, 0, 0, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 0, 2, 2, 2],
+    [0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0],
+    [0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0],
+    [0, 1, 2, 0, 2, 0, 1, 2, 1, 2, 0, 1, 0, 1, 2, 0],
+    [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, 1, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 0, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1],
+    [0, 2, 2, 0, 1, 2, 2, 1, 0, 2, 2, 0, 1, 2, 2, 1],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 0, 1, 0, 1],
+    [0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 2, 2, 2, 0, 1, 1, 1],
+    [0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 2, 1, 1, 1, 2],
+    [0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2],
+    [0, 0, 0, 2, 1, 1, 1, 2, 1, 1, 1, 2, 0, 0, 0, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2],
+    [0, 0, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2],
+    [0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1],
+    [0, 2, 2, 2, 1, 2, 2, 2, 0, 2, 2, 2, 1, 2, 2, 2],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 1, 2, 0, 1, 1, 2, 2, 0, 1, 2, 2, 2, 0],
+];
+
+// The texels whose index drops its most significant bit
+const ANCHORS_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 2, 8, 2, 2, 8, 8, 15, 2, 8,
+    2, 2, 8, 8, 2, 2, 15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6, 6, 2, 6, 8, 15, 15, 2,
+    2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+const ANCHORS_3_2: [u8; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3, 3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5,
+    15, 15, 8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15, 3, 15, 5, 5, 5, 8, 5, 10, 5,
+    10, 8, 13, 15, 12, 3, 3,
+];
+
+const ANCHORS_3_3: [u8; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8, 15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6,
+    10, 15, 15, 10, 8, 15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8, 15, 3, 15, 15, 15,
+    15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+];
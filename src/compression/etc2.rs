@@ -0,0 +1,227 @@
+// ETC2 RGBA8 block decoding: an EAC alpha block followed by an ETC2 color block.
+// EAC R11 blocks store a single channel with more precision.
+
+pub fn decode(block: &[u8; 16], texels: &mut [[u8; 4]]) {
+    let alpha = u64::from_be_bytes(block[..8].try_into().unwrap());
+    let color = u64::from_be_bytes(block[8..].try_into().unwrap());
+
+    decode_color(color, texels);
+    decode_alpha(alpha, texels);
+}
+
+pub fn decode_r11(block: &[u8; 8], texels: &mut [[u8; 4]]) {
+    let block = u64::from_be_bytes(*block);
+
+    let base = (block >> 56) as i32 * 8 + 4;
+    let multiplier = (block >> 52 & 0xf) as i32;
+    let modifiers = EAC_MODIFIERS[(block >> 48 & 0xf) as usize];
+
+    for (texel, pixel) in texels.iter_mut().enumerate().take(16) {
+        let index = (block >> (45 - 3 * column_major(texel)) & 0x7) as usize;
+
+        // A multiplier of zero still steps an eighth of the usual amount
+        let modifier = match multiplier {
+            0 => modifiers[index],
+            _ => modifiers[index] * multiplier * 8,
+        };
+
+        let value = (base + modifier).clamp(0, 2047);
+
+        *pixel = [((value * 255 + 1023) / 2047) as u8, 0, 0, 255];
+    }
+}
+
+fn decode_alpha(block: u64, texels: &mut [[u8; 4]]) {
+    let base = (block >> 56) as i32;
+    let multiplier = (block >> 52 & 0xf) as i32;
+    let modifiers = EAC_MODIFIERS[(block >> 48 & 0xf) as usize];
+
+    for (texel, pixel) in texels.iter_mut().enumerate().take(16) {
+        // Indices are stored column by column
+        let index = (block >> (45 - 3 * column_major(texel)) & 0x7) as usize;
+
+        pixel[3] = (base + modifiers[index] * multiplier).clamp(0, 255) as u8;
+    }
+}
+
+fn decode_color(block: u64, texels: &mut [[u8; 4]]) {
+    let bits = |offset: u32, count: u32| (block >> offset & ((1 << count) - 1)) as i32;
+
+    if bits(33, 1) == 0 {
+        let first = [bits(60, 4), bits(52, 4), bits(44, 4)].map(extend_4);
+        let second = [bits(56, 4), bits(48, 4), bits(40, 4)].map(extend_4);
+
+        return decode_subblocks(block, first, second, texels);
+    }
+
+    let red = bits(59, 5);
+    let green = bits(51, 5);
+    let blue = bits(43, 5);
+
+    let signed = |value: i32| (value << 29) >> 29;
+    let red_2 = red + signed(bits(56, 3));
+    let green_2 = green + signed(bits(48, 3));
+    let blue_2 = blue + signed(bits(40, 3));
+
+    if !(0..32).contains(&red_2) {
+        // T mode
+        let first = [bits(59, 2) << 2 | bits(56, 2), bits(52, 4), bits(48, 4)].map(extend_4);
+        let second = [bits(44, 4), bits(40, 4), bits(36, 4)].map(extend_4);
+        let distance = DISTANCES[(bits(34, 2) << 1 | bits(32, 1)) as usize];
+
+        let paint = [
+            first,
+            second.map(|channel| channel + distance),
+            second,
+            second.map(|channel| channel - distance),
+        ];
+
+        decode_paint(block, paint, texels);
+    } else if !(0..32).contains(&green_2) {
+        // H mode
+        let first = [
+            bits(59, 4),
+            bits(56, 3) << 1 | bits(52, 1),
+            bits(51, 1) << 3 | bits(47, 3),
+        ];
+        let second = [bits(43, 4), bits(39, 4), bits(35, 4)];
+
+        let order = |color: [i32; 3]| color[0] << 8 | color[1] << 4 | color[2];
+        let index = bits(34, 1) << 2 | bits(32, 1) << 1 | i32::from(order(first) >= order(second));
+        let distance = DISTANCES[index as usize];
+
+        let first = first.map(extend_4);
+        let second = second.map(extend_4);
+
+        let paint = [
+            first.map(|channel| channel + distance),
+            first.map(|channel| channel - distance),
+            second.map(|channel| channel + distance),
+            second.map(|channel| channel - distance),
+        ];
+
+        decode_paint(block, paint, texels);
+    } else if !(0..32).contains(&blue_2) {
+        // Planar mode
+        let extend_6 = |value: i32| value << 2 | value >> 4;
+        let extend_7 = |value: i32| value << 1 | value >> 6;
+
+        let origin = [
+            extend_6(bits(57, 6)),
+            extend_7(bits(56, 1) << 6 | bits(49, 6)),
+            extend_6(bits(48, 1) << 5 | bits(43, 2) << 3 | bits(39, 3)),
+        ];
+        let horizontal = [
+            extend_6(bits(34, 5) << 1 | bits(32, 1)),
+            extend_7(bits(25, 7)),
+            extend_6(bits(19, 6)),
+        ];
+        let vertical = [
+            extend_6(bits(13, 6)),
+            extend_7(bits(6, 7)),
+            extend_6(bits(0, 6)),
+        ];
+
+        for (texel, pixel) in texels.iter_mut().enumerate().take(16) {
+            let (x, y) = ((texel % 4) as i32, (texel / 4) as i32);
+
+            for channel in 0..3 {
+                let value = (x * (horizontal[channel] - origin[channel])
+                    + y * (vertical[channel] - origin[channel])
+                    + 4 * origin[channel]
+                    + 2)
+                    >> 2;
+
+                pixel[channel] = value.clamp(0, 255) as u8;
+            }
+        }
+    } else {
+        // Differential mode
+        let first = [red, green, blue].map(extend_5);
+        let second = [red_2, green_2, blue_2].map(extend_5);
+
+        decode_subblocks(block, first, second, texels);
+    }
+}
+
+fn decode_subblocks(block: u64, first: [i32; 3], second: [i32; 3], texels: &mut [[u8; 4]]) {
+    let is_flipped = block >> 32 & 1 == 1;
+    let tables = [
+        INTENSITY_MODIFIERS[(block >> 37 & 0x7) as usize],
+        INTENSITY_MODIFIERS[(block >> 34 & 0x7) as usize],
+    ];
+
+    for (texel, pixel) in texels.iter_mut().enumerate().take(16) {
+        let (x, y) = (texel % 4, texel / 4);
+        let subblock = usize::from(if is_flipped { y >= 2 } else { x >= 2 });
+
+        let base = if subblock == 0 { first } else { second };
+        let modifier = tables[subblock][index(block, texel)];
+
+        for channel in 0..3 {
+            pixel[channel] = (base[channel] + modifier).clamp(0, 255) as u8;
+        }
+    }
+}
+
+fn decode_paint(block: u64, paint: [[i32; 3]; 4], texels: &mut [[u8; 4]]) {
+    for (texel, pixel) in texels.iter_mut().enumerate().take(16) {
+        let color = paint[index(block, texel)];
+
+        for channel in 0..3 {
+            pixel[channel] = color[channel].clamp(0, 255) as u8;
+        }
+    }
+}
+
+fn index(block: u64, texel: usize) -> usize {
+    let position = column_major(texel);
+    let msb = block >> (16 + position) & 1;
+    let lsb = block >> position & 1;
+
+    (msb << 1 | lsb) as usize
+}
+
+fn column_major(texel: usize) -> usize {
+    (texel % 4) * 4 + texel / 4
+}
+
+fn extend_4(value: i32) -> i32 {
+    value << 4 | value
+}
+
+fn extend_5(value: i32) -> i32 {
+    value << 3 | value >> 2
+}
+
+const INTENSITY_MODIFIERS: [[i32; 4]; 8] = [
+    [2, 8, -2, -8],
+    [5, 17, -5, -17],
+    [9, 29, -9, -29],
+    [13, 42, -13, -42],
+    [18, 60, -18, -60],
+    [24, 80, -24, -80],
+    [33, 106, -33, -106],
+    [47, 183, -47, -183],
+];
+
+const DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
@@ -0,0 +1,96 @@
+use crate::card::{Compression, Layer, Region};
+
+use std::fmt;
+
+/// An error of a [`Pipeline`].
+///
+/// [`Pipeline`]: crate::Pipeline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A texture has no pixels.
+    Empty { layer: Layer },
+    /// The data of a texture does not match its dimensions.
+    ///
+    /// Compressed payloads contain every mip level; see [`Compression`].
+    InvalidLength {
+        layer: Layer,
+        expected: usize,
+        actual: usize,
+    },
+    /// A compressed payload stops before the last of its mip levels.
+    ///
+    /// See [`Compression`].
+    MissingMipLevels {
+        layer: Layer,
+        expected: u32,
+        actual: u32,
+    },
+    /// A texture is compressed with a format for a different amount of
+    /// channels.
+    ///
+    /// See [`Compression`].
+    InvalidCompression {
+        layer: Layer,
+        compression: Compression,
+    },
+    /// A card has no texture for a layer that is being updated.
+    MissingLayer { layer: Layer },
+    /// A [`Region`] is empty or does not fit in its texture.
+    InvalidRegion { layer: Layer, region: Region },
+    /// A compressed texture cannot be updated.
+    Compressed { layer: Layer },
+    /// A downscaled texture cannot be updated.
+    ///
+    /// See [`Card::downscaled`].
+    ///
+    /// [`Card::downscaled`]: crate::Card::downscaled
+    Downscaled { layer: Layer },
+    /// The shader could not be composed or compiled.
+    InvalidShader(String),
+    /// The sources of the shader could not be watched for changes.
+    Watch(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Empty { layer } => write!(f, "the {layer} texture has no pixels"),
+            Error::InvalidLength {
+                layer,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "the {layer} texture should have {expected} bytes, but it has {actual}"
+            ),
+            Error::MissingMipLevels {
+                layer,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "the {layer} texture should have {expected} mip levels, but it has {actual}"
+            ),
+            Error::InvalidCompression { layer, compression } => write!(
+                f,
+                "the {layer} texture cannot be compressed with {compression:?}"
+            ),
+            Error::MissingLayer { layer } => write!(f, "the card has no {layer} texture"),
+            Error::InvalidRegion { layer, region } => write!(
+                f,
+                "the {}x{} region at ({}, {}) does not fit in the {layer} texture",
+                region.width, region.height, region.x, region.y
+            ),
+            Error::Compressed { layer } => {
+                write!(f, "the {layer} texture is compressed and cannot be updated")
+            }
+            Error::Downscaled { layer } => {
+                write!(f, "the {layer} texture is downscaled and cannot be updated")
+            }
+            Error::InvalidShader(message) => write!(f, "invalid shader: {message}"),
+            Error::Watch(message) => write!(f, "failed to watch shader: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,134 @@
+use notify_debouncer_full::notify;
+use notify_debouncer_full::{DebounceEventResult, Debouncer, RecommendedCache, new_debouncer};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Watches WGSL files for changes.
+pub struct Watcher {
+    _raw: Debouncer<notify::RecommendedWatcher, RecommendedCache>,
+    changes: Mutex<mpsc::Receiver<Vec<(String, String)>>>,
+}
+
+impl Watcher {
+    /// Watches every WGSL file in a directory, named after their stems.
+    pub fn new(directory: PathBuf) -> Result<Self, notify::Error> {
+        let watched = directory.clone();
+
+        Self::watch(
+            &watched,
+            notify::RecursiveMode::Recursive,
+            is_wgsl,
+            move || read(&directory),
+        )
+    }
+
+    /// Watches a single file, named after the given name.
+    pub fn file(path: PathBuf, name: &'static str) -> Result<Self, notify::Error> {
+        // Editors often replace files instead of writing them, which ends the
+        // watches of the files themselves
+        let path = fs::canonicalize(&path)?;
+        let directory = path.parent().unwrap_or(&path).to_path_buf();
+
+        Self::watch(
+            &directory,
+            notify::RecursiveMode::NonRecursive,
+            {
+                let path = path.clone();
+
+                move |changed| changed == path
+            },
+            move || {
+                fs::read_to_string(&path)
+                    .map(|source| vec![(name.to_owned(), source)])
+                    .unwrap_or_default()
+            },
+        )
+    }
+
+    fn watch(
+        directory: &Path,
+        mode: notify::RecursiveMode,
+        is_watched: impl Fn(&Path) -> bool + Send + 'static,
+        read: impl Fn() -> Vec<(String, String)> + Send + 'static,
+    ) -> Result<Self, notify::Error> {
+        let (sender, receiver) = mpsc::channel();
+
+        // Files may have changed since the crate was built
+        let _ = sender.send(read());
+
+        let mut debouncer = new_debouncer(
+            Duration::from_millis(10),
+            None,
+            move |events: DebounceEventResult| {
+                let Ok(events) = events else {
+                    return;
+                };
+
+                let modified = events.iter().any(|event| {
+                    event.paths.iter().any(|path| is_watched(path))
+                        && (event.kind.is_modify()
+                            || event.kind.is_remove()
+                            || event.kind.is_create())
+                });
+
+                if modified {
+                    let _ = sender.send(read());
+                }
+            },
+        )?;
+
+        debouncer.watch(directory, mode)?;
+
+        Ok(Self {
+            _raw: debouncer,
+            changes: Mutex::new(receiver),
+        })
+    }
+
+    /// Returns the name and contents of every watched file, if any of them
+    /// changed since the last call.
+    pub fn changes(&self) -> Option<Vec<(String, String)>> {
+        self.changes.lock().ok()?.try_iter().last()
+    }
+}
+
+impl std::fmt::Debug for Watcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watcher").finish_non_exhaustive()
+    }
+}
+
+fn read(directory: &Path) -> Vec<(String, String)> {
+    let mut files = Vec::new();
+    let mut directories = vec![directory.to_path_buf()];
+
+    while let Some(directory) = directories.pop() {
+        let Ok(entries) = fs::read_dir(&directory) else {
+            continue;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+
+            if path.is_dir() {
+                directories.push(path);
+            } else if is_wgsl(&path)
+                && let Some(name) = path.file_stem().and_then(|name| name.to_str())
+                && let Ok(source) = fs::read_to_string(&path)
+            {
+                files.push((name.to_owned(), source));
+            }
+        }
+    }
+
+    files
+}
+
+fn is_wgsl(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "wgsl")
+}
@@ -1,79 +1,91 @@
 pub use bytes::Bytes;
 pub use wgpu;
 
+mod cache;
+mod compression;
+mod error;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+mod mipmap;
 mod quaternion;
+mod raymarch;
+mod shader;
 mod vector;
 
 pub mod card;
+pub mod pipeline_cache;
+pub mod software;
 
+pub use cache::CardCache;
 pub use card::Card;
+pub use error::Error;
 pub use quaternion::Quaternion;
 pub use vector::Vector;
 
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
 use std::mem;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
 
 #[derive(Debug)]
 pub struct Pipeline {
-    raw: wgpu::RenderPipeline,
+    variants: HashMap<shader::Variant, wgpu::RenderPipeline>,
+    layout: wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+    sources: shader::Sources,
+    cache: Option<wgpu::PipelineCache>,
+    #[cfg(feature = "hot-reload")]
+    watcher: Option<hot_reload::Watcher>,
+    #[cfg(feature = "hot-reload")]
+    material_watcher: Option<hot_reload::Watcher>,
+    #[cfg(feature = "hot-reload")]
+    last_good_sources: Option<shader::Sources>,
+    #[cfg(feature = "hot-reload")]
+    custom_material: bool,
+    sampler: wgpu::Sampler,
+    uniforms_layout: wgpu::BindGroupLayout,
     uniforms_binding: wgpu::BindGroup,
     textures_layout: wgpu::BindGroupLayout,
     configuration: (wgpu::Buffer, Configuration),
     _back_texture: wgpu::Texture,
 }
 
-macro_rules! load_wgsl {
-    ($path:literal, $format:expr) => {
-        $crate::wgpu::ShaderModuleDescriptor {
-            label: Some($path),
-            source: $crate::wgpu::ShaderSource::Wgsl(
-                if cfg!(all(not(target_arch = "wasm32"), debug_assertions)) {
-                    let mut shader = ::std::fs::read_to_string(format!(
-                        "{}/src/{}",
-                        env!("CARGO_MANIFEST_DIR"),
-                        $path
-                    ))
-                    .unwrap();
-
-                    shader.push_str("\n");
-
-                    shader.push_str(if $format.is_srgb() {
-                        include_str!("./shader/linear_rgb.wgsl")
-                    } else {
-                        include_str!("./shader/srgb.wgsl")
-                    });
-
-                    shader.into()
-                } else if $format.is_srgb() {
-                    concat!(
-                        include_str!($path),
-                        "\n",
-                        include_str!("./shader/linear_rgb.wgsl")
-                    )
-                    .into()
-                } else {
-                    concat!(
-                        include_str!($path),
-                        "\n",
-                        include_str!("./shader/srgb.wgsl")
-                    )
-                    .into()
-                },
-            ),
-        }
-    };
-}
-
 impl Pipeline {
+    /// Creates a [`Pipeline`] with the default [`Settings`].
+    ///
+    /// Fails if the back image is empty or its data does not match its
+    /// dimensions.
     pub fn new(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         format: wgpu::TextureFormat,
         back_texture: card::Image,
-    ) -> Self {
+    ) -> Result<Self, Error> {
+        Self::with_settings(device, queue, format, back_texture, Settings::default())
+    }
+
+    /// Creates a [`Pipeline`] with the given [`Settings`].
+    ///
+    /// Fails like [`Pipeline::new`], or if the custom material of the
+    /// [`Settings`] does not compile.
+    pub fn with_settings(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        format: wgpu::TextureFormat,
+        back_texture: card::Image,
+        settings: Settings,
+    ) -> Result<Self, Error> {
+        back_texture.validate(card::Layer::BackBase)?;
+
         let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
             label: Some("holofoil sampler"),
             mag_filter: wgpu::FilterMode::Linear,
             min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::MipmapFilterMode::Linear,
+            anisotropy_clamp: settings.anisotropy.clamp(1, 16),
             ..wgpu::SamplerDescriptor::default()
         });
 
@@ -90,7 +102,10 @@ impl Pipeline {
             bytemuck::cast_slice(&[Parameters::from(Configuration::default())]),
         );
 
-        let back_texture = back_texture.upload(device, queue);
+        let back_texture = back_texture
+            .fit(device.limits().max_texture_dimension_2d, true)
+            .0
+            .upload(device, queue);
 
         let uniforms_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("holofoil uniforms layout"),
@@ -124,42 +139,19 @@ impl Pipeline {
             ],
         });
 
-        let uniforms_binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: None,
-            layout: &uniforms_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(
-                        &back_texture.create_view(&wgpu::TextureViewDescriptor::default()),
-                    ),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: configuration.as_entire_binding(),
-                },
-            ],
-        });
+        let uniforms_binding = uniforms_binding(
+            device,
+            &uniforms_layout,
+            &sampler,
+            &back_texture,
+            &configuration,
+        );
 
         let textures_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("holofoil texture layout"),
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
+            entries: &std::array::from_fn::<_, { card::TEXTURES }, _>(|binding| {
                 wgpu::BindGroupLayoutEntry {
-                    binding: 1,
+                    binding: binding as u32,
                     visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Texture {
                         sample_type: wgpu::TextureSampleType::Float { filterable: true },
@@ -167,18 +159,8 @@ impl Pipeline {
                         multisampled: false,
                     },
                     count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
-                        view_dimension: wgpu::TextureViewDimension::D2,
-                        multisampled: false,
-                    },
-                    count: None,
-                },
-            ],
+                }
+            }),
         });
 
         let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -187,74 +169,253 @@ impl Pipeline {
             immediate_size: 0,
         });
 
-        let shader = device.create_shader_module(load_wgsl!("./shader.wgsl", format));
-
-        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("holofoil pipeline"),
-            layout: Some(&layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-                buffers: &[wgpu::VertexBufferLayout {
-                    array_stride: mem::size_of::<card::Instance>() as u64,
-                    step_mode: wgpu::VertexStepMode::Instance,
-                    attributes: &wgpu::vertex_attr_array!(
-                        // Viewport
-                        0 => Float32x4,
-                        // Size
-                        1 => Float32x2,
-                        // Rotation
-                        2 => Float32x4,
-                    ),
-                }],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-                targets: &[Some(wgpu::ColorTargetState {
+        #[cfg(feature = "hot-reload")]
+        let custom_material = settings.material.is_some();
+
+        let sources = shader::Sources::new(settings.material);
+        let variant = shader::Variant::from(Configuration::default());
+        let variants = HashMap::from([(
+            variant,
+            compile(device, false, || {
+                pipeline(
+                    device,
+                    &layout,
                     format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            cache: None,
-            multiview_mask: None,
-        });
-
-        Self {
-            raw: pipeline,
+                    settings.cache.as_ref(),
+                    &sources,
+                    variant,
+                )
+            })?,
+        )]);
+
+        Ok(Self {
+            variants,
+            layout,
+            format,
+            sources,
+            cache: settings.cache,
+            #[cfg(feature = "hot-reload")]
+            watcher: None,
+            #[cfg(feature = "hot-reload")]
+            material_watcher: None,
+            #[cfg(feature = "hot-reload")]
+            last_good_sources: None,
+            #[cfg(feature = "hot-reload")]
+            custom_material,
+            sampler,
+            uniforms_layout,
             uniforms_binding,
             textures_layout,
             configuration: (configuration, Configuration::default()),
             _back_texture: back_texture,
-        }
+        })
+    }
+
+    /// Replaces the default back of the cards.
+    ///
+    /// Fails if the image is empty or its data does not match its dimensions.
+    pub fn set_back(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        back: card::Image,
+    ) -> Result<(), Error> {
+        back.validate(card::Layer::BackBase)?;
+
+        let back_texture = back
+            .fit(device.limits().max_texture_dimension_2d, true)
+            .0
+            .upload(device, queue);
+
+        self.uniforms_binding = uniforms_binding(
+            device,
+            &self.uniforms_layout,
+            &self.sampler,
+            &back_texture,
+            &self.configuration.0,
+        );
+
+        self._back_texture = back_texture;
+
+        Ok(())
     }
 
-    pub fn configure(&self, queue: &wgpu::Queue, configuration: Configuration) {
-        let (buffer, last) = &self.configuration;
+    /// Changes the [`Configuration`] of the pipeline.
+    ///
+    /// Nothing is written if it has not changed since the last time. Shader
+    /// variants are compiled the first time a configuration needs them.
+    ///
+    /// Fails if the variant does not compile, keeping the current
+    /// configuration.
+    pub fn configure(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        configuration: Configuration,
+    ) -> Result<(), Error> {
+        if self.configuration.1 == configuration {
+            return Ok(());
+        }
+
+        let variant = shader::Variant::from(configuration);
+
+        if !self.variants.contains_key(&variant) {
+            let compile_with = |sources| {
+                compile(device, false, || {
+                    pipeline(
+                        device,
+                        &self.layout,
+                        self.format,
+                        self.cache.as_ref(),
+                        sources,
+                        variant,
+                    )
+                })
+            };
+
+            let pipeline = compile_with(&self.sources);
+
+            // Reloaded sources may only break the variants compiled afterwards
+            #[cfg(feature = "hot-reload")]
+            let pipeline = pipeline.or_else(|error| match &self.last_good_sources {
+                Some(sources) => compile_with(sources).map_err(|_| error),
+                None => Err(error),
+            });
 
-        if *last == configuration {
-            return;
+            let _ = self.variants.insert(variant, pipeline?);
         }
 
+        let (buffer, last) = &mut self.configuration;
+
         queue.write_buffer(
             buffer,
             0,
             bytemuck::cast_slice(&[Parameters::from(configuration)]),
         );
+
+        *last = configuration;
+
+        Ok(())
+    }
+
+    /// Watches a directory for changes to the WGSL sources of the shader.
+    ///
+    /// Files are matched by name: `shader.wgsl` replaces the main shader, and
+    /// `color.wgsl` and `material.wgsl` replace the modules it imports. A
+    /// custom material, of the [`Settings`] or watched with
+    /// [`Pipeline::watch_material`], is never replaced. Other files are
+    /// ignored. Changes are applied by [`Pipeline::reload`].
+    #[cfg(feature = "hot-reload")]
+    pub fn watch(&mut self, directory: impl Into<std::path::PathBuf>) -> Result<(), Error> {
+        let watcher = hot_reload::Watcher::new(directory.into())
+            .map_err(|error| Error::Watch(error.to_string()))?;
+
+        self.watcher = Some(watcher);
+
+        Ok(())
+    }
+
+    /// Watches a WGSL file for changes to the custom material.
+    ///
+    /// The file replaces the material of the [`Settings`], and follows the
+    /// same rules. Changes are applied by [`Pipeline::reload`], starting with
+    /// the current contents of the file.
+    #[cfg(feature = "hot-reload")]
+    pub fn watch_material(&mut self, path: impl Into<std::path::PathBuf>) -> Result<(), Error> {
+        let watcher = hot_reload::Watcher::file(path.into(), "material")
+            .map_err(|error| Error::Watch(error.to_string()))?;
+
+        self.material_watcher = Some(watcher);
+        self.custom_material = true;
+
+        Ok(())
     }
 
+    /// Recompiles the shader if its sources changed since the last call,
+    /// returning whether it did.
+    ///
+    /// The current shader is kept if the new one fails to compile.
+    #[cfg(feature = "hot-reload")]
+    pub fn reload(&mut self, device: &wgpu::Device) -> Result<bool, Error> {
+        let files = self.watcher.as_ref().and_then(hot_reload::Watcher::changes);
+        let material = self
+            .material_watcher
+            .as_ref()
+            .and_then(hot_reload::Watcher::changes);
+
+        if files.is_none() && material.is_none() {
+            return Ok(false);
+        }
+
+        let mut sources = self.sources.clone();
+
+        // The watched material comes last, so it wins over the directory
+        for (name, source) in files.into_iter().flatten() {
+            if name == "shader" {
+                sources.main = Cow::Owned(source);
+            } else if name == "material" && self.custom_material {
+                continue;
+            } else if let Some((_, module)) = sources
+                .modules
+                .iter_mut()
+                .find(|(module, _)| *module == name)
+            {
+                *module = Cow::Owned(source);
+            }
+        }
+
+        for (name, source) in material.into_iter().flatten() {
+            if let Some((_, module)) = sources
+                .modules
+                .iter_mut()
+                .find(|(module, _)| *module == name)
+            {
+                *module = Cow::Owned(source);
+            }
+        }
+
+        if sources == self.sources {
+            return Ok(false);
+        }
+
+        self.variants = compile(device, true, || {
+            self.variants
+                .keys()
+                .map(|&variant| {
+                    let pipeline = pipeline(
+                        device,
+                        &self.layout,
+                        self.format,
+                        self.cache.as_ref(),
+                        &sources,
+                        variant,
+                    )?;
+
+                    Ok((variant, pipeline))
+                })
+                .collect()
+        })?;
+        self.last_good_sources = Some(mem::replace(&mut self.sources, sources));
+
+        Ok(true)
+    }
+
+    /// Uploads the textures of a card.
+    ///
+    /// Textures exceeding the limits of the device are downscaled on the CPU;
+    /// see [`Card::downscaled`].
+    ///
+    /// Fails if a texture is empty or its data does not match its dimensions.
     pub fn upload(
         &self,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         definition: &card::Structure,
-    ) -> Card {
+    ) -> Result<Card, Error> {
+        definition.validate()?;
+
+        let (fitted, downscaled) = definition.fit(device.limits().max_texture_dimension_2d);
+
         let instance = device.create_buffer(&wgpu::wgt::BufferDescriptor {
             label: Some("holofoil instance buffer"),
             size: mem::size_of::<card::Instance>() as u64,
@@ -262,62 +423,167 @@ impl Pipeline {
             mapped_at_creation: false,
         });
 
-        let base = definition.base.upload(device, queue);
-        let foil = definition
-            .foil
-            .as_ref()
-            .map(|foil| foil.upload(device, queue));
-        let etching = definition
+        let base = fitted.base.upload(device, queue);
+        let foil = fitted.foil.as_ref().map(|foil| foil.upload(device, queue));
+        let etching = fitted
             .etching
             .as_ref()
             .map(|etching| etching.upload(device, queue));
+        let wear = fitted.wear.as_ref().map(|wear| wear.upload(device, queue));
+        let relief = fitted
+            .relief
+            .as_ref()
+            .map(|relief| relief.upload(device, queue));
+        let back = fitted.back.as_ref().map(|back| back.upload(device, queue));
+        let distances = fitted
+            .silhouette
+            .as_ref()
+            .map(|silhouette| silhouette.distances(&fitted.base));
+        let silhouette = distances
+            .as_ref()
+            .map(|distances| distances.upload(device, queue));
+        let label = fitted
+            .slab
+            .as_ref()
+            .map(|slab| slab.label.upload(device, queue));
 
         let base_view = base.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let foil_view = foil
-            .as_ref()
-            .map(|foil| foil.create_view(&wgpu::TextureViewDescriptor::default()));
-
-        let etching_view = etching
-            .as_ref()
-            .map(|etching| etching.create_view(&wgpu::TextureViewDescriptor::default()));
+        // Missing textures are never sampled, but they still need a binding
+        let view = |texture: Option<&wgpu::Texture>| {
+            texture
+                .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()))
+                .unwrap_or_else(|| base_view.clone())
+        };
+
+        let views: [wgpu::TextureView; card::TEXTURES] = [
+            base_view.clone(),
+            view(foil.as_ref()),
+            view(etching.as_ref()),
+            view(silhouette.as_ref()),
+            view(label.as_ref()),
+            view(wear.as_ref()),
+            view(relief.as_ref()),
+            view(back.as_ref().map(|back| &back.base)),
+            view(back.as_ref().and_then(|back| back.foil.as_ref())),
+            view(back.as_ref().and_then(|back| back.etching.as_ref())),
+        ];
+
+        let entries: Vec<_> = views
+            .iter()
+            .enumerate()
+            .map(|(binding, view)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: wgpu::BindingResource::TextureView(view),
+            })
+            .collect();
 
         let binding = device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: None,
             layout: &self.textures_layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&base_view),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(
-                        &foil_view.unwrap_or(base_view.clone()),
-                    ),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: wgpu::BindingResource::TextureView(
-                        &etching_view.unwrap_or(base_view.clone()),
-                    ),
-                },
-            ],
+            entries: &entries,
         });
 
-        Card {
+        let mut flags = 0;
+
+        if foil.is_some() {
+            flags |= card::FLAG_FOIL;
+        }
+
+        if etching.is_some() {
+            flags |= card::FLAG_ETCHING;
+        }
+
+        if silhouette.is_some() {
+            flags |= card::FLAG_SILHOUETTE;
+        }
+
+        if wear.is_some() {
+            flags |= card::FLAG_WEAR;
+        }
+
+        if relief.is_some() {
+            flags |= card::FLAG_RELIEF;
+        }
+
+        if let Some(back) = &back {
+            flags |= card::FLAG_BACK;
+
+            if back.foil.is_some() {
+                flags |= card::FLAG_BACK_FOIL;
+            }
+
+            if back.etching.is_some() {
+                flags |= card::FLAG_BACK_ETCHING;
+            }
+        }
+
+        let shell = match &definition.slab {
+            Some(slab) => {
+                flags |= card::FLAG_SLAB;
+
+                Some(slab.acrylic)
+            }
+            None => definition.sleeve,
+        };
+
+        if shell.is_some() {
+            flags |= card::FLAG_SHELL;
+        }
+
+        let silhouette_alpha = matches!(fitted.silhouette, Some(card::Silhouette::Alpha));
+
+        // Only the layers that can be updated keep their pixels
+        let updatable = |layer, compression: Option<card::Compression>| {
+            compression.is_none() && !downscaled.iter().any(|(scaled, _)| *scaled == layer)
+        };
+
+        let base_pixels = updatable(card::Layer::Base, fitted.base.compression)
+            .then(|| card::Pixels::Image(fitted.base));
+        let foil_pixels = fitted
+            .foil
+            .filter(|foil| updatable(card::Layer::Foil, foil.compression))
+            .map(card::Pixels::Mask);
+        let etching_pixels = fitted
+            .etching
+            .filter(|etching| updatable(card::Layer::Etching, etching.compression))
+            .map(card::Pixels::Mask);
+
+        Ok(Card {
             instance,
-            _base: base,
-            _foil: foil,
-            _etching: etching,
+            uploaded: None,
+            base,
+            foil,
+            etching,
+            base_pixels,
+            foil_pixels,
+            etching_pixels,
+            _silhouette: silhouette,
+            silhouette: distances,
+            silhouette_alpha,
+            _label: label,
+            _wear: wear,
+            _relief: relief,
+            _back: back,
             binding,
-            width: definition.width,
-            height: definition.base.size,
-        }
+            flags,
+            downscaled,
+            width: definition.base.width,
+            height: definition.base.height,
+            shape: definition.shape,
+            shell,
+            label: definition
+                .slab
+                .as_ref()
+                .map(|slab| slab.label_height)
+                .unwrap_or_default(),
+        })
     }
 
     pub fn render(&self, render_pass: &mut wgpu::RenderPass<'_>, card: &Card) {
-        render_pass.set_pipeline(&self.raw);
+        let variant = shader::Variant::from(self.configuration.1);
+
+        render_pass.set_pipeline(&self.variants[&variant]);
         render_pass.set_bind_group(0, &self.uniforms_binding, &[]);
         render_pass.set_bind_group(1, &card.binding, &[]);
         render_pass.set_vertex_buffer(0, card.instance.slice(..));
@@ -325,11 +591,184 @@ impl Pipeline {
     }
 }
 
+// Compiles shaders inside of an error scope, so invalid ones fail instead of
+// panicking later.
+//
+// Browsers report errors asynchronously; their shaders are trusted, unless
+// `strict`.
+fn compile<T>(
+    device: &wgpu::Device,
+    strict: bool,
+    compile: impl FnOnce() -> Result<T, Error>,
+) -> Result<T, Error> {
+    let scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let output = compile();
+
+    match now(scope.pop()) {
+        Some(Some(error)) => Err(Error::InvalidShader(error.to_string())),
+        Some(None) => output,
+        None if strict => Err(Error::InvalidShader(
+            "the shader could not be validated right away".to_owned(),
+        )),
+        None => output,
+    }
+}
+
+// Native backends report errors right away
+fn now<T>(future: impl Future<Output = T>) -> Option<T> {
+    match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None,
+    }
+}
+
+fn pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    format: wgpu::TextureFormat,
+    cache: Option<&wgpu::PipelineCache>,
+    sources: &shader::Sources,
+    variant: shader::Variant,
+) -> Result<wgpu::RenderPipeline, Error> {
+    let source = sources
+        .compose(&variant.defines(format))
+        .map_err(Error::InvalidShader)?;
+
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("holofoil shader"),
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    });
+
+    Ok(
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("holofoil pipeline"),
+            layout: Some(layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: mem::size_of::<card::Instance>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array!(
+                        // Viewport
+                        0 => Float32x4,
+                        // Size
+                        1 => Float32x2,
+                        // Rotation
+                        2 => Float32x4,
+                        // Flags
+                        3 => Uint32,
+                        // Shape
+                        4 => Uint32,
+                        // Shape parameters
+                        5 => Float32x2,
+                        // Shell
+                        6 => Float32x4,
+                        // Shell tint
+                        7 => Float32x4,
+                        // Label
+                        8 => Float32,
+                    ),
+                }],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            cache,
+            multiview_mask: None,
+        }),
+    )
+}
+
+fn uniforms_binding(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    back_texture: &wgpu::Texture,
+    configuration: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(
+                    &back_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                ),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: configuration.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Settings {
+    /// The maximum anisotropy of texture filtering, between `1` and `16`.
+    pub anisotropy: u16,
+    /// The cache of compiled pipelines, if any.
+    ///
+    /// See [`pipeline_cache`] to create one and persist it.
+    pub cache: Option<wgpu::PipelineCache>,
+    /// The WGSL source of a custom material, if any.
+    ///
+    /// It must define a `fn material(ctx: SurfaceContext) -> vec4<f32>`,
+    /// which returns the linear color of a point on the surface of the card.
+    /// The built-in material is available as `holo(ctx)`.
+    ///
+    /// ```wgsl
+    /// struct SurfaceContext {
+    ///     face: u32, // FACE_FRONT, FACE_BACK or FACE_EDGE
+    ///     uv: vec2<f32>,
+    ///     position: vec2<f32>,
+    ///     normal: vec3<f32>,
+    ///     view: vec3<f32>,
+    ///     light: vec3<f32>,
+    ///     light_strength: f32,
+    ///     base: vec4<f32>,
+    ///     foil: f32,
+    ///     etch: f32,
+    ///     wear: f32,
+    /// }
+    /// ```
+    pub material: Option<Cow<'static, str>>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            anisotropy: 16,
+            cache: None,
+            material: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Configuration {
     pub n_samples: u32,
     pub max_iterations: u32,
     pub light: Light,
+    /// Renders a [`DebugView`] instead of the material, if set.
+    pub debug_view: Option<DebugView>,
 }
 
 impl Default for Configuration {
@@ -345,10 +784,70 @@ impl Default for Configuration {
                 },
                 power: 400.0,
             },
+            debug_view: None,
         }
     }
 }
 
+/// An input or intermediate value of the shader, to find out why a card
+/// looks wrong.
+///
+/// Scalar values are shown in grayscale. Every value is stored as it is,
+/// without any color encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DebugView {
+    /// The normals of the surface, mapped from `-1..1` to `0..1`.
+    Normals = 1,
+    /// The texture coordinates, in the red and green channels.
+    Uvs,
+    /// The foil mask.
+    Foil,
+    /// The etching mask.
+    Etching,
+    /// How much of the foil is not etched.
+    Purity,
+    /// How colorful the base image is.
+    Chroma,
+    /// How bright the base image is.
+    Luminance,
+    /// The foil flakes that sparkle, and how much.
+    Flakes,
+    /// A heatmap of the raymarching iterations, from blue for few to red
+    /// for `max_iterations`.
+    Iterations,
+}
+
+impl DebugView {
+    /// Every [`DebugView`].
+    pub const ALL: [Self; 9] = [
+        Self::Normals,
+        Self::Uvs,
+        Self::Foil,
+        Self::Etching,
+        Self::Purity,
+        Self::Chroma,
+        Self::Luminance,
+        Self::Flakes,
+        Self::Iterations,
+    ];
+}
+
+impl fmt::Display for DebugView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            DebugView::Normals => "normals",
+            DebugView::Uvs => "UVs",
+            DebugView::Foil => "foil mask",
+            DebugView::Etching => "etching mask",
+            DebugView::Purity => "purity",
+            DebugView::Chroma => "chroma",
+            DebugView::Luminance => "luminance",
+            DebugView::Flakes => "flakes",
+            DebugView::Iterations => "iterations",
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Light {
     pub position: Vector,
@@ -360,7 +859,8 @@ pub struct Light {
 pub struct Parameters {
     n_samples: u32,
     max_iterations: u32,
-    _padding: [u32; 2],
+    debug_view: u32,
+    _padding: u32,
     light_position: [f32; 3],
     light_power: f32,
 }
@@ -376,7 +876,8 @@ impl From<Configuration> for Parameters {
                 configuration.light.position.z,
             ],
             light_power: configuration.light.power,
-            _padding: [0, 0],
+            debug_view: configuration.debug_view.map_or(0, |view| view as u32),
+            _padding: 0,
         }
     }
 }
@@ -0,0 +1,408 @@
+use std::sync::LazyLock;
+
+/// A rectangle of pixels of a mip level.
+#[derive(Debug, Clone)]
+pub struct Level {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+pub fn count(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+/// Generates the whole mip chain of a texture, with levels laid out one after
+/// the other.
+pub fn chain(width: u32, height: u32, format: wgpu::TextureFormat, data: &[u8]) -> Vec<u8> {
+    let channels = format.block_copy_size(None).unwrap_or(4) as usize;
+
+    let mut chain = data.to_vec();
+    let mut offset = 0;
+
+    for level in 1..count(width, height) {
+        let (previous_width, previous_height) = size(width, height, level - 1);
+        let (level_width, level_height) = size(width, height, level);
+        let length = previous_width as usize * previous_height as usize * channels;
+
+        let next = downsample(
+            &chain[offset..offset + length],
+            previous_width,
+            previous_height,
+            (0, 0, level_width, level_height),
+            format,
+        );
+
+        chain.extend_from_slice(&next);
+        offset += length;
+    }
+
+    chain
+}
+
+/// Replaces a rectangle of the first level of a mip chain, and updates the
+/// texels of every other level that depend on it.
+///
+/// Returns the updated rectangle of every level, starting with the first.
+pub fn update(
+    chain: &mut [u8],
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    first: Level,
+) -> Vec<Level> {
+    let channels = format.block_copy_size(None).unwrap_or(4) as usize;
+    let count = count(width, height);
+
+    let mut levels = Vec::with_capacity(count as usize);
+    let mut rectangle = first;
+    let mut offset = 0;
+
+    for level in 0..count {
+        let (level_width, level_height) = size(width, height, level);
+        let length = level_width as usize * level_height as usize * channels;
+        let pixels = &mut chain[offset..offset + length];
+
+        for (j, row) in rectangle
+            .data
+            .chunks_exact(rectangle.width as usize * channels)
+            .enumerate()
+        {
+            let start = ((rectangle.y as usize + j) * level_width as usize + rectangle.x as usize)
+                * channels;
+
+            pixels[start..start + row.len()].copy_from_slice(row);
+        }
+
+        let next = (level + 1 < count).then(|| {
+            let (next_width, next_height) = size(width, height, level + 1);
+
+            // Texels are averages of their 2x2 footprint in the previous level
+            let footprint = |start: u32, length: u32, size: u32| {
+                let first = (start / 2).min(size - 1);
+                let end = (start + length).div_ceil(2).clamp(first + 1, size);
+
+                (first, end - first)
+            };
+
+            let (x, width) = footprint(rectangle.x, rectangle.width, next_width);
+            let (y, height) = footprint(rectangle.y, rectangle.height, next_height);
+
+            Level {
+                x,
+                y,
+                width,
+                height,
+                data: downsample(
+                    pixels,
+                    level_width,
+                    level_height,
+                    (x, y, width, height),
+                    format,
+                ),
+            }
+        });
+
+        levels.push(rectangle);
+        offset += length;
+
+        match next {
+            Some(next) => rectangle = next,
+            None => break,
+        }
+    }
+
+    levels
+}
+
+fn size(width: u32, height: u32, level: u32) -> (u32, u32) {
+    ((width >> level).max(1), (height >> level).max(1))
+}
+
+// Averages the texels of a whole level into a rectangle of the next one
+fn downsample(
+    level: &[u8],
+    level_width: u32,
+    level_height: u32,
+    (x, y, width, height): (u32, u32, u32, u32),
+    format: wgpu::TextureFormat,
+) -> Vec<u8> {
+    let channels = format.block_copy_size(None).unwrap_or(4) as usize;
+    let is_srgb = format.is_srgb();
+
+    let decode = |channel: usize, value: u8| {
+        if is_srgb && channel < 3 {
+            SRGB_TO_LINEAR[value as usize]
+        } else {
+            f32::from(value) / 255.0
+        }
+    };
+
+    let encode = |channel: usize, value: f32| {
+        let value = if is_srgb && channel < 3 {
+            linear_to_srgb(value)
+        } else {
+            value
+        };
+
+        (value.clamp(0.0, 1.0) * 255.0).round() as u8
+    };
+
+    // Source texels are clamped to the edges of the level
+    let source = |i: u32, j: u32| {
+        let i = i.min(level_width - 1) as usize;
+        let j = j.min(level_height - 1) as usize;
+
+        (j * level_width as usize + i) * channels
+    };
+
+    let mut data = Vec::with_capacity(width as usize * height as usize * channels);
+
+    for j in y..y + height {
+        for i in x..x + width {
+            let texels = [
+                source(2 * i, 2 * j),
+                source(2 * i + 1, 2 * j),
+                source(2 * i, 2 * j + 1),
+                source(2 * i + 1, 2 * j + 1),
+            ];
+
+            for channel in 0..channels {
+                let sum: f32 = texels
+                    .iter()
+                    .map(|texel| decode(channel, level[texel + channel]))
+                    .sum();
+
+                data.push(encode(channel, sum / 4.0));
+            }
+        }
+    }
+
+    data
+}
+
+/// Resizes an image with a Lanczos filter, blending colors in linear space.
+pub fn resize(
+    width: u32,
+    height: u32,
+    new_width: u32,
+    new_height: u32,
+    format: wgpu::TextureFormat,
+    data: &[u8],
+) -> Vec<u8> {
+    let channels = format.block_copy_size(None).unwrap_or(4) as usize;
+    let is_srgb = format.is_srgb();
+    let (width, new_width) = (width as usize, new_width as usize);
+
+    let decoded: Vec<f32> = data
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| {
+            if is_srgb && i % channels < 3 {
+                SRGB_TO_LINEAR[value as usize]
+            } else {
+                f32::from(value) / 255.0
+            }
+        })
+        .collect();
+
+    // Rows first, then columns
+    let mut rows = vec![0.0; new_width * height as usize * channels];
+
+    for (x, (start, weights)) in lanczos(width as u32, new_width as u32).enumerate() {
+        for y in 0..height as usize {
+            for channel in 0..channels {
+                rows[(y * new_width + x) * channels + channel] = weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, weight)| {
+                        weight * decoded[(y * width + start + i) * channels + channel]
+                    })
+                    .sum();
+            }
+        }
+    }
+
+    let mut resized = vec![0; new_width * new_height as usize * channels];
+
+    for (y, (start, weights)) in lanczos(height, new_height).enumerate() {
+        for x in 0..new_width {
+            for channel in 0..channels {
+                let value: f32 = weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, weight)| {
+                        weight * rows[((start + i) * new_width + x) * channels + channel]
+                    })
+                    .sum();
+
+                let value = if is_srgb && channel < 3 {
+                    linear_to_srgb(value.max(0.0))
+                } else {
+                    value
+                };
+
+                resized[(y * new_width + x) * channels + channel] =
+                    (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+            }
+        }
+    }
+
+    resized
+}
+
+// The first source pixel and the normalized Lanczos-3 weights of every target
+// pixel along an axis
+fn lanczos(source: u32, target: u32) -> impl Iterator<Item = (usize, Vec<f32>)> {
+    const RADIUS: f32 = 3.0;
+
+    let kernel = |x: f32| {
+        if x == 0.0 {
+            1.0
+        } else if x.abs() < RADIUS {
+            let x = x * std::f32::consts::PI;
+
+            RADIUS * x.sin() * (x / RADIUS).sin() / (x * x)
+        } else {
+            0.0
+        }
+    };
+
+    let scale = source as f32 / target as f32;
+    let stretch = scale.max(1.0);
+    let support = RADIUS * stretch;
+
+    (0..target).map(move |i| {
+        let center = (i as f32 + 0.5) * scale;
+        let start = (center - support).floor().max(0.0) as usize;
+        let end = ((center + support).ceil() as usize).min(source as usize);
+
+        let mut weights: Vec<f32> = (start..end)
+            .map(|j| kernel((j as f32 + 0.5 - center) / stretch))
+            .collect();
+
+        let sum: f32 = weights.iter().sum();
+        weights.iter_mut().for_each(|weight| *weight /= sum);
+
+        (start, weights)
+    })
+}
+
+pub static SRGB_TO_LINEAR: LazyLock<[f32; 256]> = LazyLock::new(|| {
+    std::array::from_fn(|value| {
+        let value = value as f32 / 255.0;
+
+        if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    })
+});
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_match_a_whole_chain() {
+        let (width, height) = (37, 21);
+
+        for format in [
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            wgpu::TextureFormat::R8Unorm,
+        ] {
+            let channels = format.block_copy_size(None).unwrap() as usize;
+            let mut pixels: Vec<u8> = (0..width * height * channels as u32)
+                .map(|i| (i * 7919 % 251) as u8)
+                .collect();
+
+            let mut updated = chain(width, height, format, &pixels);
+
+            // A stroke, the corners and the whole first level
+            for (x, y, region_width, region_height) in [
+                (5, 3, 1, 1),
+                (36, 0, 1, 21),
+                (0, 20, 4, 1),
+                (11, 6, 9, 7),
+                (0, 0, width, height),
+            ] {
+                let data: Vec<u8> = (0..region_width * region_height * channels as u32)
+                    .map(|i| (i * 31 + x * 17 + y) as u8)
+                    .collect();
+
+                for (j, row) in data
+                    .chunks_exact(region_width as usize * channels)
+                    .enumerate()
+                {
+                    let start = ((y as usize + j) * width as usize + x as usize) * channels;
+                    pixels[start..start + row.len()].copy_from_slice(row);
+                }
+
+                let levels = update(
+                    &mut updated,
+                    width,
+                    height,
+                    format,
+                    Level {
+                        x,
+                        y,
+                        width: region_width,
+                        height: region_height,
+                        data,
+                    },
+                );
+
+                assert_eq!(levels.len(), count(width, height) as usize);
+                assert_eq!(
+                    updated,
+                    chain(width, height, format, &pixels),
+                    "{format:?} ({x}, {y}) {region_width}x{region_height}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn resize_keeps_flat_colors() {
+        for (format, pixel) in [
+            (wgpu::TextureFormat::Rgba8UnormSrgb, vec![200, 90, 17, 128]),
+            (wgpu::TextureFormat::R8Unorm, vec![77]),
+        ] {
+            let data = pixel.repeat(40 * 25);
+
+            // Down, up and along a single axis
+            for (new_width, new_height) in [(13, 8), (57, 31), (40, 9)] {
+                let resized = resize(40, 25, new_width, new_height, format, &data);
+
+                assert_eq!(
+                    resized,
+                    pixel.repeat((new_width * new_height) as usize),
+                    "{format:?} {new_width}x{new_height}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lanczos_weights_are_normalized() {
+        for (source, target) in [(100, 7), (7, 100), (64, 64)] {
+            for (start, weights) in lanczos(source, target) {
+                let sum: f32 = weights.iter().sum();
+
+                assert!((sum - 1.0).abs() < 1e-5, "{source} -> {target}: {sum}");
+                assert!(start + weights.len() <= source as usize);
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Persist compiled pipelines between launches.
+//!
+//! Pipeline caches are only supported by some backends, like Vulkan, and
+//! require the [`wgpu::Features::PIPELINE_CACHE`] feature. Every helper
+//! returns `None` otherwise.
+
+/// Creates an empty [`wgpu::PipelineCache`].
+pub fn new(device: &wgpu::Device) -> Option<wgpu::PipelineCache> {
+    // SAFETY: There is no data to trust
+    unsafe { create(device, None) }
+}
+
+/// Creates a [`wgpu::PipelineCache`] from the bytes of a previous
+/// [`save`].
+///
+/// Data produced by another version of `wgpu`, adapter, or driver is
+/// ignored, and the cache starts empty.
+///
+/// # Safety
+/// The `data` must have been produced by [`save`] and not be tampered with,
+/// since `wgpu` cannot fully validate it.
+pub unsafe fn load(device: &wgpu::Device, data: &[u8]) -> Option<wgpu::PipelineCache> {
+    // SAFETY: Guaranteed by the caller
+    unsafe { create(device, Some(data)) }
+}
+
+/// Serializes the pipelines compiled so far into bytes.
+pub fn save(cache: &wgpu::PipelineCache) -> Option<Vec<u8>> {
+    cache.get_data()
+}
+
+/// A key that identifies the adapter a cache is compatible with, useful as
+/// the name of its file.
+pub fn key(adapter: &wgpu::AdapterInfo) -> Option<String> {
+    wgpu::util::pipeline_cache_key(adapter)
+}
+
+unsafe fn create(device: &wgpu::Device, data: Option<&[u8]>) -> Option<wgpu::PipelineCache> {
+    if !device.features().contains(wgpu::Features::PIPELINE_CACHE) {
+        return None;
+    }
+
+    // SAFETY: Guaranteed by the caller
+    let cache = unsafe {
+        device.create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+            label: Some("holofoil pipeline cache"),
+            data,
+            fallback: true,
+        })
+    };
+
+    Some(cache)
+}
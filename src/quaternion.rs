@@ -24,6 +24,20 @@ impl Quaternion {
         }
     }
 
+    pub fn conjugate(self) -> Self {
+        Self {
+            a: -self.a,
+            w: self.w,
+        }
+    }
+
+    /// Rotates a [`Vector`], like the shader does.
+    pub fn rotate(self, vector: Vector) -> Vector {
+        let u = self.a;
+
+        vector + u.cross(u.cross(vector) + vector * self.w) * 2.0
+    }
+
     pub fn to_euler(self) -> Vector {
         let pitch = (2.0 * (self.w * self.a.x - self.a.y * self.a.z))
             .clamp(-1.0, 1.0)
@@ -0,0 +1,306 @@
+//! The geometry of the shader, mirrored on the CPU.
+//!
+//! Every function here must match its namesake in `shader.wgsl`.
+use crate::card::{Mask, Shape, Silhouette, Sleeve, Viewport};
+use crate::{Quaternion, Vector};
+
+use std::f32::consts::PI;
+
+pub const MAX_DISTANCE: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Surface {
+    Card,
+    Shell,
+}
+
+/// The shape of a card in the scene, like the `Card` struct of the shader.
+#[derive(Debug, Clone, Copy)]
+pub struct Geometry<'a> {
+    // Half of the dimensions of the card, in scene units
+    pub size: (f32, f32),
+    pub offset: (f32, f32),
+    pub shape: Shape,
+    pub shell: Option<Sleeve>,
+    pub label: Option<f32>,
+    pub silhouette: Option<&'a Mask>,
+}
+
+impl<'a> Geometry<'a> {
+    /// Creates the [`Geometry`] of a card with the given dimensions in
+    /// pixels.
+    ///
+    /// Slabs have a `label`, and they are fitted in the viewport.
+    pub fn new(
+        width: u32,
+        height: u32,
+        shape: Shape,
+        shell: Option<Sleeve>,
+        label: Option<f32>,
+        silhouette: Option<&'a Mask>,
+    ) -> Self {
+        let max_dimension = width.max(height) as f32;
+
+        let geometry = Self {
+            size: (
+                width as f32 / (2.0 * max_dimension),
+                height as f32 / (2.0 * max_dimension),
+            ),
+            offset: (0.0, 0.0),
+            shape,
+            shell,
+            label,
+            silhouette,
+        };
+
+        if geometry.label.is_some() {
+            geometry.fit_slab()
+        } else {
+            geometry
+        }
+    }
+
+    // Scales down and moves the card so its whole slab fits in the viewport
+    fn fit_slab(self) -> Self {
+        let shell = self.shell.unwrap_or(Sleeve::NONE);
+        let width = 2.0 * self.size.0;
+        let padding = (shell.margin + shell.thickness) * width;
+        let label = (self.label.unwrap_or_default() + shell.margin) * width;
+
+        let size = (self.size.0 + padding, self.size.1 + padding + label / 2.0);
+        let scale = self.size.0.max(self.size.1) / size.0.max(size.1);
+
+        Self {
+            size: (self.size.0 * scale, self.size.1 * scale),
+            offset: (0.0, -label / 2.0 * scale),
+            ..self
+        }
+    }
+
+    /// Returns the texture coordinates of a point on a face of the card.
+    pub fn texture_uv(&self, x: f32, y: f32) -> (f32, f32) {
+        (0.5 + x / (2.0 * self.size.0), 0.5 - y / (2.0 * self.size.1))
+    }
+
+    pub fn sd_surface(&self, p: Vector, surface: Surface) -> f32 {
+        match surface {
+            Surface::Card => self.sd_card(Vector {
+                x: p.x - self.offset.0,
+                y: p.y - self.offset.1,
+                z: p.z,
+            }),
+            Surface::Shell => self.sd_shell(p),
+        }
+    }
+
+    fn sd_card(&self, p: Vector) -> f32 {
+        let mut outline = self.sd_shape(p.x, p.y);
+
+        if let Some(silhouette) = self.silhouette {
+            outline = outline.max(self.sd_silhouette(p.x, p.y, silhouette));
+        }
+
+        extrude(p, outline, self.size.0 / 220.0)
+    }
+
+    fn sd_shell(&self, p: Vector) -> f32 {
+        let shell = self.shell.unwrap_or(Sleeve::NONE);
+        let width = 2.0 * self.size.0;
+        let margin = shell.margin * width;
+        let thickness = shell.thickness * width;
+
+        let mut center = self.offset;
+        let mut size = (
+            self.size.0 + margin + thickness,
+            self.size.1 + margin + thickness,
+        );
+
+        // Slabs have room for a label on top
+        if let Some(label) = self.label {
+            let label = label * width + margin;
+
+            center.1 += label / 2.0;
+            size.1 += label / 2.0;
+        }
+
+        extrude(
+            p,
+            sd_rounded_box(p.x - center.0, p.y - center.1, size, margin + thickness),
+            self.size.0 / 220.0 + thickness,
+        )
+    }
+
+    // Shape parameters are relative to the width of the card
+    fn sd_shape(&self, x: f32, y: f32) -> f32 {
+        let size = self.size;
+        let width = 2.0 * size.0;
+        let min_size = size.0.min(size.1);
+
+        match self.shape {
+            Shape::RoundedRectangle { radius } => {
+                sd_rounded_box(x, y, size, (radius * width).min(min_size))
+            }
+            Shape::Circle { ridges } => sd_ridged_circle(x, y, min_size, ridges),
+            Shape::Polygon { sides, radius } => {
+                let radius = radius * width;
+
+                sd_regular_polygon(x, y, min_size - radius, sides.max(3)) - radius
+            }
+            Shape::Capsule => sd_rounded_box(x, y, size, min_size),
+        }
+    }
+
+    // The silhouette is a signed distance field in pixels, spanning the whole texture
+    fn sd_silhouette(&self, x: f32, y: f32, silhouette: &Mask) -> f32 {
+        let (u, v) = self.texture_uv(x, y);
+        let value = sample(silhouette, u, v);
+        let height = silhouette.height as f32;
+
+        (value - 0.5) * 2.0 * Silhouette::SPREAD / height * 2.0 * self.size.1
+    }
+
+    pub fn estimate_normal(&self, p: Vector, surface: Surface) -> Vector {
+        let eps = 0.00001;
+        let d = |offset: Vector| {
+            self.sd_surface(p + offset, surface) - self.sd_surface(p - offset, surface)
+        };
+
+        Vector {
+            x: d(Vector::X * eps),
+            y: d(Vector::Y * eps),
+            z: d(Vector::Z * eps),
+        }
+        .normalize()
+    }
+}
+
+/// Returns the ray of the camera through a point of the viewport, in pixels.
+pub fn ray(viewport: Viewport, x: f32, y: f32) -> (Vector, Vector) {
+    let width = viewport.width as f32;
+    let height = viewport.height as f32;
+
+    let pixel = Vector {
+        x: (2.0 * (x - viewport.x as f32) - width) / height,
+        y: (-2.0 * (y - viewport.y as f32) + height) / height,
+        z: 3.0,
+    };
+
+    (
+        Vector {
+            x: 0.0,
+            y: 0.0,
+            z: -MAX_DISTANCE,
+        },
+        pixel.normalize(),
+    )
+}
+
+pub fn march(
+    origin: Vector,
+    direction: Vector,
+    start: f32,
+    rotation: Quaternion,
+    geometry: &Geometry<'_>,
+    surface: Surface,
+    max_iterations: u32,
+) -> f32 {
+    let inverse = rotation.conjugate();
+    let mut t = start;
+
+    for _ in 0..max_iterations {
+        let p = inverse.rotate(origin + direction * t);
+        let d = geometry.sd_surface(p, surface);
+
+        if d < 0.00001 || t > 2.0 * MAX_DISTANCE {
+            break;
+        }
+
+        t += d;
+    }
+
+    t
+}
+
+/// Refracts a unit vector through a surface, like `refract` in WGSL.
+pub fn refract(incident: Vector, normal: Vector, eta: f32) -> Vector {
+    let cosine = normal.dot(incident);
+    let k = 1.0 - eta * eta * (1.0 - cosine * cosine);
+
+    if k < 0.0 {
+        return Vector::default();
+    }
+
+    incident * eta - normal * (eta * cosine + k.sqrt())
+}
+
+// Bilinear sampling with clamped edges, like the sampler of the shader
+fn sample(mask: &Mask, u: f32, v: f32) -> f32 {
+    let width = mask.width as usize;
+    let height = mask.height as usize;
+
+    let x = (u * mask.width as f32 - 0.5).clamp(0.0, (width - 1) as f32);
+    let y = (v * mask.height as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x.fract(), y.fract());
+
+    let pixel = |x: usize, y: usize| f32::from(mask.pixels[y * width + x]) / 255.0;
+
+    let top = pixel(x0, y0) * (1.0 - fx) + pixel(x1, y0) * fx;
+    let bottom = pixel(x0, y1) * (1.0 - fx) + pixel(x1, y1) * fx;
+
+    top * (1.0 - fy) + bottom * fy
+}
+
+fn sd_rounded_box(x: f32, y: f32, b: (f32, f32), r: f32) -> f32 {
+    let q = (x.abs() - b.0 + r, y.abs() - b.1 + r);
+
+    q.0.max(q.1).min(0.0) + length(q.0.max(0.0), q.1.max(0.0)) - r
+}
+
+fn sd_ridged_circle(x: f32, y: f32, r: f32, ridges: u32) -> f32 {
+    let d = length(x, y) - r;
+
+    if ridges == 0 {
+        return d;
+    }
+
+    let depth = r / ridges as f32 * 0.3;
+    let angle = y.atan2(x);
+
+    d + depth * (0.5 + 0.5 * (ridges as f32 * angle).cos())
+}
+
+// Regular polygon with a vertex on top, inscribed in a circle of radius `r`
+fn sd_regular_polygon(x: f32, y: f32, r: f32, n: u32) -> f32 {
+    let sector = PI / n as f32;
+    let sector_direction = (sector.cos(), sector.sin());
+
+    let angle = x.atan2(y) + sector;
+    let local_angle = angle - 2.0 * sector * (angle / (2.0 * sector)).floor() - sector;
+
+    let length = length(x, y);
+    let mut q = (
+        length * local_angle.cos() - r * sector_direction.0,
+        length * local_angle.sin().abs() - r * sector_direction.1,
+    );
+    q.1 += (-q.1).clamp(0.0, r * sector_direction.1);
+
+    self::length(q.0, q.1) * sign(q.0)
+}
+
+fn extrude(p: Vector, sdf: f32, h: f32) -> f32 {
+    let w = (sdf, p.z.abs() - h);
+
+    w.0.max(w.1).min(0.0) + length(w.0.max(0.0), w.1.max(0.0))
+}
+
+fn length(x: f32, y: f32) -> f32 {
+    x.hypot(y)
+}
+
+// Unlike `f32::signum`, zero has no sign
+fn sign(x: f32) -> f32 {
+    if x == 0.0 { 0.0 } else { x.signum() }
+}
@@ -0,0 +1,207 @@
+use crate::Configuration;
+
+use std::borrow::Cow;
+
+/// The features of the shader that are specialized at compile time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Variant {
+    pub supersampling: bool,
+    pub debug: bool,
+}
+
+impl Variant {
+    pub fn defines(self, format: wgpu::TextureFormat) -> Vec<&'static str> {
+        let mut defines = Vec::new();
+
+        if self.supersampling {
+            defines.push("SUPERSAMPLING");
+        }
+
+        if self.debug {
+            defines.push("DEBUG");
+        }
+
+        if format.is_srgb() {
+            defines.push("SRGB_TARGET");
+        }
+
+        defines
+    }
+}
+
+impl From<Configuration> for Variant {
+    fn from(configuration: Configuration) -> Self {
+        Self {
+            supersampling: configuration.n_samples > 1,
+            debug: configuration.debug_view.is_some(),
+        }
+    }
+}
+
+/// The WGSL sources of the shader and the modules it can import.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sources {
+    pub main: Cow<'static, str>,
+    pub modules: Vec<(&'static str, Cow<'static, str>)>,
+}
+
+impl Sources {
+    pub fn new(material: Option<Cow<'static, str>>) -> Self {
+        Self {
+            main: Cow::Borrowed(include_str!("./shader.wgsl")),
+            modules: vec![
+                ("color", Cow::Borrowed(include_str!("./shader/color.wgsl"))),
+                (
+                    "material",
+                    material.unwrap_or(Cow::Borrowed(include_str!("./shader/material.wgsl"))),
+                ),
+            ],
+        }
+    }
+
+    /// Composes the main shader with the modules it imports.
+    ///
+    /// Two kinds of directives are supported, on lines of their own:
+    ///
+    /// - `#import name` expands into the module with the given name, unless it
+    ///   was already imported.
+    /// - `#ifdef NAME`, `#ifndef NAME`, `#else` and `#endif` keep the lines of
+    ///   a block only if `NAME` is, or is not, defined.
+    pub fn compose(&self, defines: &[&str]) -> Result<String, String> {
+        let modules: Vec<_> = self
+            .modules
+            .iter()
+            .map(|(name, source)| (*name, source.as_ref()))
+            .collect();
+
+        compose(&self.main, &modules, defines)
+    }
+}
+
+fn compose(source: &str, modules: &[(&str, &str)], defines: &[&str]) -> Result<String, String> {
+    let mut output = String::with_capacity(source.len());
+    let mut imported = Vec::new();
+
+    expand(source, modules, defines, &mut imported, &mut output)?;
+
+    Ok(output)
+}
+
+fn expand<'a>(
+    source: &str,
+    modules: &[(&'a str, &'a str)],
+    defines: &[&str],
+    imported: &mut Vec<&'a str>,
+    output: &mut String,
+) -> Result<(), String> {
+    // Whether the enclosing block is kept, and the condition of each block
+    let mut blocks: Vec<(bool, bool)> = Vec::new();
+
+    for line in source.lines() {
+        let is_kept = blocks
+            .last()
+            .is_none_or(|&(parent, condition)| parent && condition);
+
+        let Some(directive) = line.trim_start().strip_prefix('#') else {
+            if is_kept {
+                output.push_str(line);
+                output.push('\n');
+            }
+
+            continue;
+        };
+
+        let (name, argument) = directive
+            .split_once(char::is_whitespace)
+            .map(|(name, argument)| (name, argument.trim()))
+            .unwrap_or((directive.trim(), ""));
+
+        match name {
+            "ifdef" => blocks.push((is_kept, defines.contains(&argument))),
+            "ifndef" => blocks.push((is_kept, !defines.contains(&argument))),
+            "else" => {
+                let (_, condition) = blocks.last_mut().ok_or("#else without #ifdef")?;
+
+                *condition = !*condition;
+            }
+            "endif" => {
+                let _ = blocks.pop().ok_or("#endif without #ifdef")?;
+            }
+            "import" if is_kept => {
+                let &(module, source) = modules
+                    .iter()
+                    .find(|(module, _)| *module == argument)
+                    .ok_or_else(|| format!("unknown shader module: {argument}"))?;
+
+                if !imported.contains(&module) {
+                    imported.push(module);
+
+                    expand(source, modules, defines, imported, output)?;
+                }
+            }
+            "import" => {}
+            _ => return Err(format!("unknown shader directive: #{name}")),
+        }
+    }
+
+    if !blocks.is_empty() {
+        return Err("#ifdef without #endif".to_owned());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_variant_is_valid() {
+        let sources = Sources::new(None);
+
+        for supersampling in [false, true] {
+            for debug in [false, true] {
+                for format in [
+                    wgpu::TextureFormat::Rgba8Unorm,
+                    wgpu::TextureFormat::Rgba8UnormSrgb,
+                ] {
+                    let variant = Variant {
+                        supersampling,
+                        debug,
+                    };
+                    let shader = sources.compose(&variant.defines(format)).unwrap();
+
+                    validate(&shader)
+                        .unwrap_or_else(|error| panic!("{variant:?} {format:?}: {error}"));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn directives_are_expanded() {
+        let modules = [("a", "#import b\na\n"), ("b", "b\n")];
+        let source =
+            "#import a\n#import b\n#ifdef X\n#ifndef Y\nx\n#else\ny\n#endif\n#else\nz\n#endif\n";
+
+        assert_eq!(compose(source, &modules, &["X"]).unwrap(), "b\na\nx\n");
+        assert_eq!(compose(source, &modules, &["X", "Y"]).unwrap(), "b\na\ny\n");
+        assert_eq!(compose(source, &modules, &["Y"]).unwrap(), "b\na\nz\n");
+        assert!(compose("#import c\n", &modules, &[]).is_err());
+        assert!(compose("#ifdef X\n", &modules, &[]).is_err());
+    }
+
+    fn validate(shader: &str) -> Result<(), String> {
+        let module =
+            naga::front::wgsl::parse_str(shader).map_err(|error| error.emit_to_string(shader))?;
+
+        let _ = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .map_err(|error| error.emit_to_string(shader))?;
+
+        Ok(())
+    }
+}
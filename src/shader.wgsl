@@ -1,3 +1,6 @@
+#import color
+#import material
+
 @group(0) @binding(0) var u_sampler: sampler;
 @group(0) @binding(1) var u_back: texture_2d<f32>;
 @group(0) @binding(2) var<uniform> u_params: Parameters;
@@ -5,11 +8,47 @@
 @group(1) @binding(0) var u_base: texture_2d<f32>;
 @group(1) @binding(1) var u_foil: texture_2d<f32>;
 @group(1) @binding(2) var u_etch: texture_2d<f32>;
+@group(1) @binding(3) var u_silhouette: texture_2d<f32>;
+@group(1) @binding(4) var u_label: texture_2d<f32>;
+@group(1) @binding(5) var u_wear: texture_2d<f32>;
+@group(1) @binding(6) var u_relief: texture_2d<f32>;
+@group(1) @binding(7) var u_card_back: texture_2d<f32>;
+@group(1) @binding(8) var u_back_foil: texture_2d<f32>;
+@group(1) @binding(9) var u_back_etch: texture_2d<f32>;
+
+const MAX_DISTANCE: f32 = 2.0;
+
+const FLAG_SILHOUETTE: u32 = 1u;
+const FLAG_SHELL: u32 = 2u;
+const FLAG_SLAB: u32 = 4u;
+const FLAG_WEAR: u32 = 8u;
+const FLAG_RELIEF: u32 = 16u;
+const FLAG_BACK: u32 = 32u;
+const FLAG_FOIL: u32 = 64u;
+const FLAG_ETCHING: u32 = 128u;
+const FLAG_BACK_FOIL: u32 = 256u;
+const FLAG_BACK_ETCHING: u32 = 512u;
+
+const SURFACE_CARD: u32 = 0u;
+const SURFACE_SHELL: u32 = 1u;
+
+const FACE_FRONT: u32 = 0u;
+const FACE_BACK: u32 = 1u;
+const FACE_EDGE: u32 = 2u;
+
+const SHAPE_ROUNDED_RECTANGLE: u32 = 0u;
+const SHAPE_CIRCLE: u32 = 1u;
+const SHAPE_POLYGON: u32 = 2u;
+const SHAPE_CAPSULE: u32 = 3u;
+
+const FLAKE_SIZE: f32 = 600.0;
+const FLAKE_REDUCTION: f32 = 0.1;
 
 struct Parameters {
     n_samples: u32,
     max_iterations: u32,
-    _padding: vec2<u32>,
+    debug_view: u32,
+    _padding: u32,
     light: Light,
 }
 
@@ -22,6 +61,12 @@ struct VertexInput {
     @location(0) viewport: vec4<f32>,
     @location(1) size: vec2<f32>,
     @location(2) rotation: vec4<f32>,
+    @location(3) flags: u32,
+    @location(4) shape: u32,
+    @location(5) shape_parameters: vec2<f32>,
+    @location(6) shell: vec4<f32>,
+    @location(7) shell_tint: vec4<f32>,
+    @location(8) label: f32,
     @builtin(vertex_index) index: u32,
 }
 
@@ -30,6 +75,28 @@ struct VertexOutput {
     @location(0) @interpolate(flat) viewport: vec4<f32>,
     @location(1) @interpolate(flat) size: vec2<f32>,
     @location(2) @interpolate(flat) rotation: vec4<f32>,
+    @location(3) @interpolate(flat) flags: u32,
+    @location(4) @interpolate(flat) shape: u32,
+    @location(5) @interpolate(flat) shape_parameters: vec2<f32>,
+    @location(6) @interpolate(flat) shell: vec4<f32>,
+    @location(7) @interpolate(flat) shell_tint: vec4<f32>,
+    @location(8) @interpolate(flat) label: f32,
+}
+
+struct Card {
+    size: vec2<f32>,
+    flags: u32,
+    shape: u32,
+    shape_parameters: vec2<f32>,
+    // Margin, thickness, refraction index and gloss
+    shell: vec4<f32>,
+    shell_tint: vec4<f32>,
+    // Height of the slab label
+    label: f32,
+    // Position of the card inside of its slab
+    offset: vec2<f32>,
+    // Size of a pixel on the image plane, to pick texture levels of detail
+    pixel: f32,
 }
 
 @vertex
@@ -42,18 +109,25 @@ fn vs_main(input: VertexInput) -> VertexOutput {
     out.viewport = input.viewport;
     out.size = input.size;
     out.rotation = input.rotation;
+    out.flags = input.flags;
+    out.shape = input.shape;
+    out.shape_parameters = input.shape_parameters;
+    out.shell = input.shell;
+    out.shell_tint = input.shell_tint;
+    out.label = input.label;
 
     return out;
 }
 
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    const max_distance: f32 = 2.0;
-
+#ifdef SUPERSAMPLING
     let n_samples = u_params.n_samples;
+#else
+    let n_samples = 1u;
+#endif
     let max_iterations = u_params.max_iterations;
-    let light = u_params.light;
-    let camera = vec3(0.0, 0.0, -max_distance);
+    let camera = vec3(0.0, 0.0, -MAX_DISTANCE);
 
     let position = input.position;
     let viewport = input.viewport;
@@ -62,131 +136,529 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
 
     let max_dimension = f32(max(size.x, size.y));
     let card_size = size / (2.0 * max_dimension);
+    var card = Card(
+        card_size,
+        input.flags,
+        input.shape,
+        input.shape_parameters,
+        input.shell,
+        input.shell_tint,
+        input.label,
+        vec2(0.0),
+        2.0 / viewport.w,
+    );
+
+    if (card.flags & FLAG_SLAB) != 0u {
+        card = fit_slab(card);
+    }
 
     var color: vec4<f32>;
 
     for (var m = u32(0); m < n_samples; m++) {
     for (var n = u32(0); n < n_samples; n++) {
         let o = vec2(f32(m), f32(n)) / f32(n_samples) - 0.5;
-        let ray_origin = camera;
+        var ray_origin = camera;
 
         let pixel = vec2<f32>(
             2.0 * (position.x - viewport.x + o.x) - viewport.z,
             -2.0 * (position.y - viewport.y + o.y) + viewport.w,
         ) / viewport.w;
 
-        let ray_direction = normalize(vec3(pixel, 3.0));
+        var ray_direction = normalize(vec3(pixel, 3.0));
+        var t = -MAX_DISTANCE;
 
-        var t = -max_distance;
+        var glare = vec4(0.0);
+        var transmission = vec3(1.0);
 
-        for (var i = u32(0); i < max_iterations; i++) {
-            let p = rotate_i(rotation, ray_origin + ray_direction * t);
-            let d = sd_card(p, card_size);
+        if (card.flags & FLAG_SHELL) != 0u {
+            t = march(ray_origin, ray_direction, t, rotation, card, SURFACE_SHELL, max_iterations);
 
-            if d < 0.00001 || t > 2.0 * max_distance {
-                break;
+            if t > 2.0 * MAX_DISTANCE {
+                continue;
             }
 
-            t += d;
-        }
-
-        if t <= 2.0 * max_distance {
-            let hit_rotated = ray_origin + ray_direction * t;
-            let hit = rotate_i(rotation, hit_rotated);
-            let normal = estimate_normal(hit, card_size);
-            let normal_abs = abs(normal);
-            let N = rotate(rotation, normal);
-            let V = -ray_direction;
-            let L = normalize(light.position - hit_rotated);
-            let light_strength = light.power / pow(distance(light.position, hit_rotated), 2.0);
-            let light_angle = clamp(dot(N, normalize(L + V)), 0.0, 1.0);
-
-            var sample: vec4<f32>;
-            var specular_color = vec3(1.0, 1.0, 1.0);
-            var foil_color: vec3<f32>;
-
-            if (normal_abs.z > normal_abs.x && normal_abs.z > normal_abs.y) {
-                let local_uv = hit.xy / (2.0 * card_size) + vec2(0.5, 0.5);
-                let uv_offset = vec2(0.5, 0.5) - card_size;
-                var final_uv = uv_offset + local_uv * card_size * 2.0;
-                final_uv.y = 1.0 - final_uv.y;
-
-                if (normal.z < 0.0) {
-                    // Front
-                    sample = textureSampleLevel(u_base, u_sampler, final_uv, 0.0);
-
-                    let lumi = luminance(sample.xyz);
-                    let max_channel = max(max(sample.x, sample.y), sample.z);
-                    let chroma = (max_channel - min(min(sample.x, sample.y), sample.z)) / max_channel;
-                    let etch = textureSampleLevel(u_etch, u_sampler, final_uv, 0.0).r;
-                    let foil = textureSampleLevel(u_foil, u_sampler, final_uv, 0.0).r;
-                    let purity = clamp(foil - 4.0 * etch, 0.0, 1.0);
-
-                    if foil > 0.1 {
-                        let strength = pow(light_angle, 128.0) * (1.0 - etch * 0.3) ;
-                        let angle = clamp(dot(N, L), 0.0, 1.0);
-
-                        foil_color = (sample.xyz + iridescence(angle, 1000, 5.0) * 0.4) * strength * foil;
-                        specular_color = vec3(0.0, 0.0, 0.0);
-
-                        // Foil flakes
-                        // Inspired by https://www.4rknova.com/blog/2025/08/30/foil-sticker
-                        if purity > 0.2 && chroma > 0.3 && lumi > 0.1 {
-                            let uFlakeReduction = 0.1;
-                            let uFlakeSize = 600.0;
-
-                            // Procedural flake mask
-                            let flake = hash(floor(local_uv * uFlakeSize));
-                            let flakeMask = smoothstep(uFlakeReduction, 1.0, flake);
- 
-                            // Perturbed flake normal
-                            let angleOffset = (hash(vec2(flake, flake + 3.0)) - 0.5) * 2.0;
-                            let perturbedNormal = normalize(N + vec3(angleOffset, 0.0, angleOffset));
- 
-                            // Reflection for sparkle
-                            let PR = reflect(-V, perturbedNormal);
- 
-                            // Dynamic flicker factor (only brightens, never darkens)
-                            let flakePhase = hash(floor(local_uv * uFlakeSize) + floor(PR.xy * 15.0));
-                            let phaseMod = mix(1.0, 1.8, flakePhase);
-        
-                            // Core sparkle factor (glimmer preserved)
-                            var flakeSpec = pow(clamp(dot(perturbedNormal, V) * 0.5 + 0.5, 0.0, 1.0), 128.0);
-                            // flakeSpec = max(flakeSpec, 0.1); // always visible
- 
-                            let flakeIri = iridescence(dot(perturbedNormal, V), 10000, 0.0);
- 
-                            // Final intensity
-                            var flakeIntensity = flakeMask * purity * flakeSpec * phaseMod;
-                            flakeIntensity = clamp(flakeIntensity, 0.0, 1.0);
-
-                            foil_color += flakeIri * flakeIntensity;
-                        }
-
-                        foil_color *= light_strength;
-                    }
-                } else {
-                    // Back
-                    sample = textureSampleLevel(u_back, u_sampler, vec2(1.0 - final_uv.x, final_uv.y), 0.0);
+            let layer = shade_shell(ray_origin, ray_direction, t, rotation, card);
+
+            glare = layer.glare;
+            transmission = layer.transmission;
+            ray_origin = ray_origin + ray_direction * t;
+            ray_direction = layer.direction;
+            t = 0.0;
+        }
+
+        t = march(ray_origin, ray_direction, t, rotation, card, SURFACE_CARD, max_iterations);
+
+        var sample = vec4(0.0);
+
+        if t <= 2.0 * MAX_DISTANCE {
+            sample = shade(ray_origin, ray_direction, t, rotation, card);
+        } else if (card.flags & FLAG_SLAB) != 0u {
+            sample = shade_label(ray_origin, ray_direction, rotation, card);
+        }
+
+#ifdef DEBUG
+        // The shell would hide what is being debugged
+        if u_params.debug_view != DEBUG_NONE {
+            color += sample;
+            continue;
+        }
+#endif
+
+        if sample.a > 0.0 {
+            color += vec4(sample.rgb * transmission + glare.rgb, max(sample.a, glare.a));
+        } else {
+            color += vec4(glare.rgb / max(glare.a, 0.001), glare.a);
+        }
+    }
+    }
+
+    color /= f32(n_samples * n_samples);
+
+#ifdef DEBUG
+    if u_params.debug_view == DEBUG_ITERATIONS {
+        let iterations = f32(debug_iterations) / f32(n_samples * n_samples * max_iterations);
+
+        return encodeDebug(vec4(heatmap(iterations), 1.0));
+    }
+
+    if u_params.debug_view != DEBUG_NONE {
+        return encodeDebug(color);
+    }
+#endif
+
+    return encodeColor(color);
+}
+
+fn march(
+    origin: vec3<f32>,
+    direction: vec3<f32>,
+    start: f32,
+    rotation: vec4<f32>,
+    card: Card,
+    surface: u32,
+    max_iterations: u32,
+) -> f32 {
+    var t = start;
+
+    for (var i = u32(0); i < max_iterations; i++) {
+#ifdef DEBUG
+        debug_iterations += 1u;
+#endif
+
+        let p = rotate_i(rotation, origin + direction * t);
+        let d = sd_surface(p, card, surface);
+
+        if d < 0.00001 || t > 2.0 * MAX_DISTANCE {
+            break;
+        }
+
+        t += d;
+    }
+
+    return t;
+}
+
+fn shade(
+    ray_origin: vec3<f32>,
+    ray_direction: vec3<f32>,
+    t: f32,
+    rotation: vec4<f32>,
+    card: Card,
+) -> vec4<f32> {
+    let light = u_params.light;
+    let hit_rotated = ray_origin + ray_direction * t;
+    let hit = rotate_i(rotation, hit_rotated);
+    let normal = estimate_normal(hit, card, SURFACE_CARD);
+    let normal_abs = abs(normal);
+
+    var ctx: SurfaceContext;
+    ctx.normal = rotate(rotation, normal);
+    ctx.view = -ray_direction;
+    ctx.light = normalize(light.position - hit_rotated);
+    ctx.light_strength = light.power / pow(distance(light.position, hit_rotated), 2.0);
+
+    if (normal_abs.z > normal_abs.x && normal_abs.z > normal_abs.y) {
+        let position = hit.xy - card.offset;
+        let local_uv = position / (2.0 * card.size) + vec2(0.5, 0.5);
+        let final_uv = texture_uv(position, card);
+        let footprint = footprint(ray_direction, hit_rotated, rotation, vec3(0.0, 0.0, sign(normal.z)), card.pixel);
+        let texture_scale = vec2(1.0, -1.0) / (2.0 * card.size);
+        let gradient = Gradient(footprint.dx * texture_scale, footprint.dy * texture_scale);
+
+        if (normal.z < 0.0) {
+            // Front
+            ctx.face = FACE_FRONT;
+            ctx.uv = final_uv;
+            ctx.position = local_uv;
+
+            if (card.flags & FLAG_RELIEF) != 0u {
+                let relief = textureSampleGrad(u_relief, u_sampler, final_uv, gradient.dx, gradient.dy).xyz * 2.0 - 1.0;
+
+                // The front faces towards -z
+                ctx.normal = rotate(rotation, normalize(vec3(relief.x, relief.y, -relief.z)));
+            }
+
+            if (card.flags & FLAG_WEAR) != 0u {
+                ctx.wear = textureSampleGrad(u_wear, u_sampler, final_uv, gradient.dx, gradient.dy).r;
+            }
+
+            ctx.base = textureSampleGrad(u_base, u_sampler, final_uv, gradient.dx, gradient.dy);
+
+            if (card.flags & FLAG_FOIL) != 0u {
+                ctx.foil = textureSampleGrad(u_foil, u_sampler, final_uv, gradient.dx, gradient.dy).r;
+            }
+
+            if (card.flags & FLAG_ETCHING) != 0u {
+                ctx.etch = textureSampleGrad(u_etch, u_sampler, final_uv, gradient.dx, gradient.dy).r;
+            }
+        } else {
+            // Back
+            let back_uv = vec2(1.0 - final_uv.x, final_uv.y);
+            let back_gradient = Gradient(gradient.dx * vec2(-1.0, 1.0), gradient.dy * vec2(-1.0, 1.0));
+
+            ctx.face = FACE_BACK;
+            ctx.uv = back_uv;
+            ctx.position = vec2(1.0 - local_uv.x, local_uv.y);
+
+            if (card.flags & FLAG_BACK) != 0u {
+                ctx.base = textureSampleGrad(u_card_back, u_sampler, back_uv, back_gradient.dx, back_gradient.dy);
+
+                if (card.flags & FLAG_BACK_FOIL) != 0u {
+                    ctx.foil = textureSampleGrad(u_back_foil, u_sampler, back_uv, back_gradient.dx, back_gradient.dy).r;
+                }
+
+                if (card.flags & FLAG_BACK_ETCHING) != 0u {
+                    ctx.etch = textureSampleGrad(u_back_etch, u_sampler, back_uv, back_gradient.dx, back_gradient.dy).r;
                 }
             } else {
-                // Side edge
-                sample = vec4(0.5, 0.5, 0.5, abs(N.z));
+                ctx.base = textureSampleGrad(u_back, u_sampler, back_uv, back_gradient.dx, back_gradient.dy);
             }
+        }
+    } else {
+        // Side edge
+        ctx.face = FACE_EDGE;
+        ctx.base = vec4(0.5, 0.5, 0.5, abs(ctx.normal.z));
+    }
+
+#ifdef DEBUG
+    if u_params.debug_view != DEBUG_NONE {
+        return debug(ctx);
+    }
+#endif
+
+    return material(ctx);
+}
+
+// Derivatives of texture coordinates between neighboring pixels
+struct Gradient {
+    dx: vec2<f32>,
+    dy: vec2<f32>,
+}
+
+// Displacement on a flat face of the card between neighboring pixels,
+// following the ray differentials of the camera
+fn footprint(
+    ray_direction: vec3<f32>,
+    hit_rotated: vec3<f32>,
+    rotation: vec4<f32>,
+    normal: vec3<f32>,
+    pixel: f32,
+) -> Gradient {
+    let d = ray_direction;
+    let N = rotate(rotation, normal);
+    let t = distance(hit_rotated, vec3(0.0, 0.0, -MAX_DISTANCE));
+
+    // Grazing angles blow up the footprint, which only picks the coarsest level
+    let cosine = dot(d, N);
+    let denominator = select(-1.0, 1.0, cosine >= 0.0) * max(abs(cosine), 0.01);
+
+    let dd_x = (vec3(pixel, 0.0, 0.0) - d * d.x * pixel) * d.z / 3.0;
+    let dd_y = (vec3(0.0, pixel, 0.0) - d * d.y * pixel) * d.z / 3.0;
+
+    let dp_x = t * (dd_x - d * dot(dd_x, N) / denominator);
+    let dp_y = t * (dd_y - d * dot(dd_y, N) / denominator);
+
+    return Gradient(rotate_i(rotation, dp_x).xy, rotate_i(rotation, dp_y).xy);
+}
+
+// Everything a material needs to know about a point on the surface of the card
+struct SurfaceContext {
+    // One of the `FACE_*` constants
+    face: u32,
+    // Texture coordinates, with the origin on the top left corner
+    uv: vec2<f32>,
+    // Coordinates on the face, with the origin on the bottom left corner
+    position: vec2<f32>,
+    // Unit vectors pointing out of the surface, towards the camera and
+    // towards the light
+    normal: vec3<f32>,
+    view: vec3<f32>,
+    light: vec3<f32>,
+    light_strength: f32,
+    // Samples of the base image and the foil, etching and wear masks
+    base: vec4<f32>,
+    foil: f32,
+    etch: f32,
+    wear: f32,
+}
+
+// The built-in holographic material
+fn holo(ctx: SurfaceContext) -> vec4<f32> {
+    let sample = ctx.base;
+    let N = ctx.normal;
+    let V = ctx.view;
+    let L = ctx.light;
+    let light_strength = ctx.light_strength;
+    let light_angle = clamp(dot(N, normalize(L + V)), 0.0, 1.0);
+    let wear = ctx.wear;
+    let etch = ctx.etch;
+    let foil = ctx.foil;
+
+    var specular_color = vec3(1.0, 1.0, 1.0);
+    var foil_color = vec3(0.0);
+
+    if foil > 0.1 {
+        let properties = foil_properties(ctx);
+        let purity = properties.purity;
+
+        // Worn foil is duller and scatters light in a wider lobe
+        let strength = pow(light_angle, mix(128.0, 24.0, wear)) * (1.0 - etch * 0.3) * (1.0 - wear * 0.5);
+        let angle = clamp(dot(N, L), 0.0, 1.0);
+
+        foil_color = (sample.xyz + iridescence(angle, 1000, 5.0) * 0.4) * strength * foil;
+        specular_color = vec3(0.0, 0.0, 0.0);
+
+        // Foil flakes
+        // Inspired by https://www.4rknova.com/blog/2025/08/30/foil-sticker
+        if has_flakes(properties) {
+            // Procedural flake mask
+            let flake = hash(floor(ctx.position * FLAKE_SIZE));
+            let flakeMask = smoothstep(FLAKE_REDUCTION, 1.0, flake);
+
+            // Perturbed flake normal
+            let angleOffset = (hash(vec2(flake, flake + 3.0)) - 0.5) * 2.0;
+            let perturbedNormal = normalize(N + vec3(angleOffset, 0.0, angleOffset));
+
+            // Reflection for sparkle
+            let PR = reflect(-V, perturbedNormal);
+
+            // Dynamic flicker factor (only brightens, never darkens)
+            let flakePhase = hash(floor(ctx.position * FLAKE_SIZE) + floor(PR.xy * 15.0));
+            let phaseMod = mix(1.0, 1.8, flakePhase);
+
+            // Core sparkle factor (glimmer preserved)
+            var flakeSpec = pow(clamp(dot(perturbedNormal, V) * 0.5 + 0.5, 0.0, 1.0), 128.0);
+            // flakeSpec = max(flakeSpec, 0.1); // always visible
+
+            let flakeIri = iridescence(dot(perturbedNormal, V), 10000, 0.0);
+
+            // Final intensity
+            var flakeIntensity = flakeMask * purity * flakeSpec * phaseMod * (1.0 - wear);
+            flakeIntensity = clamp(flakeIntensity, 0.0, 1.0);
+
+            foil_color += flakeIri * flakeIntensity;
+        }
+
+        foil_color *= light_strength;
+    }
+
+    let ambient = 0.2;
+    let diffusion = clamp(dot(N, L), 0.0, 1.0) * light_strength;
+    let specular = pow(light_angle, mix(16.0, 4.0, wear)) * light_strength * 0.02;
+
+    // Scratches catch the light, even on top of foil
+    let scratches = wear * pow(light_angle, 2.0) * light_strength * 0.4;
+
+    return vec4(sample.xyz * (ambient + diffusion) + specular_color * specular + scratches + foil_color, sample.a);
+}
+
+// How the base image and the masks under a foil affect its look
+struct FoilProperties {
+    luminance: f32,
+    chroma: f32,
+    purity: f32,
+}
+
+fn foil_properties(ctx: SurfaceContext) -> FoilProperties {
+    let sample = ctx.base;
+    let max_channel = max(max(sample.x, sample.y), sample.z);
+
+    var properties: FoilProperties;
+    properties.luminance = luminance(sample.xyz);
+    properties.chroma = (max_channel - min(min(sample.x, sample.y), sample.z)) / max(max_channel, 0.0001);
+    properties.purity = clamp(ctx.foil - 4.0 * ctx.etch, 0.0, 1.0);
 
+    return properties;
+}
 
-            let ambient = 0.2;
-            let diffusion = clamp(dot(N, L), 0.0, 1.0) * light_strength;
-            let specular = pow(light_angle, 16.0) * light_strength * 0.02;
+// Only pure, colorful and bright foil sparkles
+fn has_flakes(properties: FoilProperties) -> bool {
+    return properties.purity > 0.2 && properties.chroma > 0.3 && properties.luminance > 0.1;
+}
 
-            color += vec4(sample.xyz * (ambient + diffusion) + specular_color * specular + foil_color, sample.a);
+#ifdef DEBUG
+const DEBUG_NONE: u32 = 0u;
+const DEBUG_NORMALS: u32 = 1u;
+const DEBUG_UVS: u32 = 2u;
+const DEBUG_FOIL: u32 = 3u;
+const DEBUG_ETCHING: u32 = 4u;
+const DEBUG_PURITY: u32 = 5u;
+const DEBUG_CHROMA: u32 = 6u;
+const DEBUG_LUMINANCE: u32 = 7u;
+const DEBUG_FLAKES: u32 = 8u;
+const DEBUG_ITERATIONS: u32 = 9u;
+
+// Steps taken by every march of the current pixel
+var<private> debug_iterations: u32;
+
+// Shows one of the inputs or intermediate values of the material
+fn debug(ctx: SurfaceContext) -> vec4<f32> {
+    let properties = foil_properties(ctx);
+    var value = vec3(0.0);
+
+    switch u_params.debug_view {
+        case DEBUG_NORMALS {
+            value = ctx.normal * 0.5 + 0.5;
+        }
+        case DEBUG_UVS {
+            value = vec3(ctx.uv, 0.0);
+        }
+        case DEBUG_FOIL {
+            value = vec3(ctx.foil);
+        }
+        case DEBUG_ETCHING {
+            value = vec3(ctx.etch);
+        }
+        case DEBUG_PURITY {
+            value = vec3(properties.purity);
+        }
+        case DEBUG_CHROMA {
+            value = vec3(properties.chroma);
+        }
+        case DEBUG_LUMINANCE {
+            value = vec3(properties.luminance);
+        }
+        case DEBUG_FLAKES {
+            if ctx.foil > 0.1 && has_flakes(properties) {
+                value = vec3(smoothstep(FLAKE_REDUCTION, 1.0, hash(floor(ctx.position * FLAKE_SIZE))));
+            }
+        }
+        default {
+            return material(ctx);
         }
     }
+
+    return vec4(value, 1.0);
+}
+
+// From blue for few iterations, through green and yellow, to red for many
+fn heatmap(t: f32) -> vec3<f32> {
+    let x = clamp(t, 0.0, 1.0);
+
+    return clamp(vec3(4.0 * x - 2.0, 2.0 - abs(4.0 * x - 2.0), 2.0 - 4.0 * x), vec3(0.0), vec3(1.0));
+}
+#endif
+
+// The label of a slab lies on the same plane as the card
+fn shade_label(
+    ray_origin: vec3<f32>,
+    ray_direction: vec3<f32>,
+    rotation: vec4<f32>,
+    card: Card,
+) -> vec4<f32> {
+    let light = u_params.light;
+    let origin = rotate_i(rotation, ray_origin);
+    let direction = rotate_i(rotation, ray_direction);
+
+    if abs(direction.z) < 0.00001 {
+        return vec4(0.0);
     }
 
-    color /= f32(n_samples * n_samples);
+    let t = -origin.z / direction.z;
 
-    return encodeColor(color);
+    if t < 0.0 {
+        return vec4(0.0);
+    }
+
+    let width = 2.0 * card.size.x;
+    let margin = card.shell.x * width;
+    let height = card.label * width;
+
+    let hit = origin + direction * t;
+    let center = card.offset + vec2(0.0, card.size.y + margin + height / 2.0);
+    let uv = (hit.xy - center) / vec2(width, height);
+
+    if any(abs(uv) > vec2(0.5)) {
+        return vec4(0.0);
+    }
+
+    let hit_rotated = ray_origin + ray_direction * t;
+    let normal = vec3(0.0, 0.0, -sign(direction.z));
+    let footprint = footprint(ray_direction, hit_rotated, rotation, normal, card.pixel);
+    let scale = 1.0 / vec2(width, -height);
+
+    let sample = textureSampleGrad(
+        u_label,
+        u_sampler,
+        vec2(uv.x + 0.5, 0.5 - uv.y),
+        footprint.dx.xy * scale,
+        footprint.dy.xy * scale,
+    );
+
+    let N = rotate(rotation, normal);
+    let L = normalize(light.position - hit_rotated);
+    let light_strength = light.power / pow(distance(light.position, hit_rotated), 2.0);
+    let diffusion = clamp(dot(N, L), 0.0, 1.0) * light_strength;
+
+    return vec4(sample.rgb * (0.2 + diffusion), sample.a);
+}
+
+struct Layer {
+    glare: vec4<f32>,
+    transmission: vec3<f32>,
+    direction: vec3<f32>,
+}
+
+// A transparent plastic shell around the card
+fn shade_shell(
+    ray_origin: vec3<f32>,
+    ray_direction: vec3<f32>,
+    t: f32,
+    rotation: vec4<f32>,
+    card: Card,
+) -> Layer {
+    let light = u_params.light;
+    let refraction = card.shell.z;
+    let gloss = card.shell.w;
+    let tint = card.shell_tint;
+
+    let hit_rotated = ray_origin + ray_direction * t;
+    let hit = rotate_i(rotation, hit_rotated);
+    let normal = estimate_normal(hit, card, SURFACE_SHELL);
+    let N = rotate(rotation, normal);
+    let V = -ray_direction;
+    let L = normalize(light.position - hit_rotated);
+    let H = normalize(L + V);
+    let light_strength = light.power / pow(distance(light.position, hit_rotated), 2.0);
+
+    // Schlick's approximation of the Fresnel term
+    let f0 = pow((refraction - 1.0) / (refraction + 1.0), 2.0);
+    let fresnel = f0 + (1.0 - f0) * pow(1.0 - clamp(dot(N, V), 0.0, 1.0), 5.0);
+
+    let R = reflect(ray_direction, N);
+    let environment = mix(vec3(0.02), vec3(0.3), 0.5 + 0.5 * R.y);
+    let specular = pow(clamp(dot(N, H), 0.0, 1.0), mix(16.0, 512.0, gloss)) * light_strength * gloss;
+
+    // The edges of the shell look through more plastic
+    let edge = (1.0 - abs(normal.z)) * tint.a;
+    let reflection = environment * fresnel * gloss + vec3(specular);
+
+    var layer: Layer;
+    layer.glare = vec4(reflection + tint.rgb * edge, clamp(fresnel + specular + edge, 0.0, 1.0));
+    layer.transmission = mix(vec3(1.0), tint.rgb, edge) * (1.0 - fresnel);
+    layer.direction = refract(ray_direction, N, 1.0 / refraction);
+
+    return layer;
 }
 
 // Compute the normalized quad coordinates based on the vertex index.
@@ -197,8 +669,97 @@ fn corner_position(vertex_index: u32) -> vec2<u32> {
     return vec2<u32>((vec2(1u, 2u) + vertex_index) % vec2(6u) < vec2(3u));
 }
 
-fn sd_card(p: vec3<f32>, size: vec2<f32>) -> f32 {
-    return extrude(p, sd_rounded_box(p.xy, size, size.x / 20.0), size.x / 220.0);
+fn sd_surface(p: vec3<f32>, card: Card, surface: u32) -> f32 {
+    if surface == SURFACE_SHELL {
+        return sd_shell(p, card);
+    }
+
+    return sd_card(p - vec3(card.offset, 0.0), card);
+}
+
+fn sd_card(p: vec3<f32>, card: Card) -> f32 {
+    let size = card.size;
+    var outline = sd_shape(p.xy, card);
+
+    if (card.flags & FLAG_SILHOUETTE) != 0u {
+        outline = max(outline, sd_silhouette(p.xy, card));
+    }
+
+    return extrude(p, outline, size.x / 220.0);
+}
+
+fn sd_shell(p: vec3<f32>, card: Card) -> f32 {
+    let width = 2.0 * card.size.x;
+    let margin = card.shell.x * width;
+    let thickness = card.shell.y * width;
+
+    var center = card.offset;
+    var size = card.size + margin + thickness;
+
+    // Slabs have room for a label on top
+    if (card.flags & FLAG_SLAB) != 0u {
+        let label = card.label * width + margin;
+
+        center.y += label / 2.0;
+        size.y += label / 2.0;
+    }
+
+    return extrude(p, sd_rounded_box(p.xy - center, size, margin + thickness), card.size.x / 220.0 + thickness);
+}
+
+// Scales down and moves the card so its whole slab fits in the viewport
+fn fit_slab(card: Card) -> Card {
+    let width = 2.0 * card.size.x;
+    let padding = (card.shell.x + card.shell.y) * width;
+    let label = (card.label + card.shell.x) * width;
+
+    let size = card.size + padding + vec2(0.0, label / 2.0);
+    let scale = max(card.size.x, card.size.y) / max(size.x, size.y);
+
+    var fitted = card;
+    fitted.size = card.size * scale;
+    fitted.offset = vec2(0.0, -label / 2.0 * scale);
+
+    return fitted;
+}
+
+// Shape parameters are relative to the width of the card
+fn sd_shape(p: vec2<f32>, card: Card) -> f32 {
+    let size = card.size;
+    let width = 2.0 * size.x;
+    let parameters = card.shape_parameters;
+
+    switch card.shape {
+        case SHAPE_CIRCLE {
+            return sd_ridged_circle(p, min(size.x, size.y), u32(parameters.x));
+        }
+        case SHAPE_POLYGON {
+            let radius = parameters.y * width;
+
+            return sd_regular_polygon(p, min(size.x, size.y) - radius, u32(parameters.x)) - radius;
+        }
+        case SHAPE_CAPSULE {
+            return sd_rounded_box(p, size, min(size.x, size.y));
+        }
+        default {
+            return sd_rounded_box(p, size, min(parameters.x * width, min(size.x, size.y)));
+        }
+    }
+}
+
+// The silhouette is a signed distance field in pixels, spanning the whole texture
+fn sd_silhouette(p: vec2<f32>, card: Card) -> f32 {
+    const silhouette_spread: f32 = 16.0;
+
+    let value = textureSampleLevel(u_silhouette, u_sampler, texture_uv(p, card), 0.0).r;
+    let height = f32(textureDimensions(u_silhouette).y);
+
+    return (value - 0.5) * 2.0 * silhouette_spread / height * 2.0 * card.size.y;
+}
+
+// Card textures span the whole card
+fn texture_uv(p: vec2<f32>, card: Card) -> vec2<f32> {
+    return vec2(0.5, 0.5) + vec2(p.x, -p.y) / (2.0 * card.size);
 }
 
 fn sd_rounded_box(p: vec2<f32>, b: vec2<f32>, r: f32) -> f32 {
@@ -206,18 +767,48 @@ fn sd_rounded_box(p: vec2<f32>, b: vec2<f32>, r: f32) -> f32 {
     return min(max(q.x, q.y), 0.0) + length(max(q, vec2(0.0))) - r;
 }
 
+fn sd_ridged_circle(p: vec2<f32>, r: f32, ridges: u32) -> f32 {
+    let d = length(p) - r;
+
+    if ridges == 0u {
+        return d;
+    }
+
+    let depth = r / f32(ridges) * 0.3;
+    let angle = atan2(p.y, p.x);
+
+    return d + depth * (0.5 + 0.5 * cos(f32(ridges) * angle));
+}
+
+// Regular polygon with a vertex on top, inscribed in a circle of radius `r`
+fn sd_regular_polygon(p: vec2<f32>, r: f32, n: u32) -> f32 {
+    const pi: f32 = 3.14159265;
+
+    let sector = pi / f32(n);
+    let sector_direction = vec2(cos(sector), sin(sector));
+
+    let angle = atan2(p.x, p.y) + sector;
+    let local_angle = angle - 2.0 * sector * floor(angle / (2.0 * sector)) - sector;
+
+    var q = length(p) * vec2(cos(local_angle), abs(sin(local_angle)));
+    q -= r * sector_direction;
+    q.y += clamp(-q.y, 0.0, r * sector_direction.y);
+
+    return length(q) * sign(q.x);
+}
+
 fn extrude(p: vec3<f32>, sdf: f32, h: f32) -> f32 {
     let w = vec2(sdf, abs(p.z) - h);
   	return min(max(w.x, w.y), 0.0) + length(max(w, vec2(0.0)));
 }
 
-fn estimate_normal(p: vec3<f32>, size: vec2<f32>) -> vec3<f32> {
+fn estimate_normal(p: vec3<f32>, card: Card, surface: u32) -> vec3<f32> {
     let eps = 0.00001;
 
     return normalize(vec3(
-        sd_card(p + vec3(eps, 0, 0), size) - sd_card(p - vec3(eps, 0, 0), size),
-        sd_card(p + vec3(0, eps, 0), size) - sd_card(p - vec3(0, eps, 0), size),
-        sd_card(p + vec3(0, 0, eps), size) - sd_card(p - vec3(0, 0, eps), size)
+        sd_surface(p + vec3(eps, 0, 0), card, surface) - sd_surface(p - vec3(eps, 0, 0), card, surface),
+        sd_surface(p + vec3(0, eps, 0), card, surface) - sd_surface(p - vec3(0, eps, 0), card, surface),
+        sd_surface(p + vec3(0, 0, eps), card, surface) - sd_surface(p - vec3(0, 0, eps), card, surface)
     ));
 }
 
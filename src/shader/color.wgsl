@@ -0,0 +1,46 @@
+#ifdef SRGB_TARGET
+// The target encodes colors on its own
+fn encodeColor(c: vec4<f32>) -> vec4<f32> {
+    return c;
+}
+
+// Debug values are stored as they are
+fn encodeDebug(c: vec4<f32>) -> vec4<f32> {
+    return vec4(
+        degamma(c.r),
+        degamma(c.g),
+        degamma(c.b),
+        c.a,
+    );
+}
+
+fn degamma(u: f32) -> f32 {
+    return select(
+        u / 12.92,
+        pow((u + 0.055) / 1.055, 2.4),
+        u > 0.04045
+    );
+}
+#else
+fn encodeColor(c: vec4<f32>) -> vec4<f32> {
+    return vec4(
+        gamma(c.r),
+        gamma(c.g),
+        gamma(c.b),
+        c.a,
+    );
+}
+
+// Debug values are stored as they are
+fn encodeDebug(c: vec4<f32>) -> vec4<f32> {
+    return c;
+}
+
+fn gamma(u: f32) -> f32 {
+    return select(
+        12.92 * u,
+        1.055 * pow(u, 1.0 / 2.4) - 0.055,
+        u > 0.0031308
+    );
+}
+#endif
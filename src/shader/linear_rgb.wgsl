@@ -1,3 +0,0 @@
-fn encodeColor(c: vec4<f32>) -> vec4<f32> {
-    return c;
-}
@@ -0,0 +1,3 @@
+fn material(ctx: SurfaceContext) -> vec4<f32> {
+    return holo(ctx);
+}
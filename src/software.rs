@@ -0,0 +1,884 @@
+//! Render cards on the CPU.
+//!
+//! The software renderer mirrors the shader of a [`Pipeline`] with the
+//! built-in material. It is much slower, but it can render thumbnails
+//! without a GPU and check the math of the shader in tests.
+//!
+//! [`Pipeline`]: crate::Pipeline
+use crate::card::{self, Image, Mask, Relief, Shape, Sleeve, Structure};
+use crate::mipmap;
+use crate::raymarch::{self, Geometry, MAX_DISTANCE, Surface};
+use crate::{Configuration, Error, Quaternion, Vector};
+
+use std::num::NonZeroUsize;
+use std::thread;
+
+// Must match the constants of the shader
+const FLAKE_SIZE: f32 = 600.0;
+const FLAKE_REDUCTION: f32 = 0.1;
+
+/// A card ready to be rendered on the CPU.
+#[derive(Debug, Clone)]
+pub struct Card {
+    width: u32,
+    height: u32,
+    shape: Shape,
+    shell: Option<Sleeve>,
+    label_height: Option<f32>,
+    silhouette: Option<Mask>,
+    base: Texture,
+    foil: Option<Texture>,
+    etching: Option<Texture>,
+    wear: Option<Texture>,
+    relief: Option<Texture>,
+    label: Option<Texture>,
+    back: Back,
+}
+
+impl Card {
+    /// Decodes the textures of a card, using the `back` image if it has no
+    /// [`Back`](card::Back) of its own.
+    ///
+    /// Fails if a texture is empty or its data does not match its dimensions.
+    pub fn new(structure: &Structure, back: &Image) -> Result<Self, Error> {
+        structure.validate()?;
+
+        let back = match &structure.back {
+            Some(back) => Back {
+                base: Texture::image(&back.base),
+                foil: back.foil.as_ref().map(Texture::mask),
+                etching: back.etching.as_ref().map(Texture::mask),
+            },
+            None => {
+                back.validate(card::Layer::BackBase)?;
+
+                Back {
+                    base: Texture::image(back),
+                    foil: None,
+                    etching: None,
+                }
+            }
+        };
+
+        Ok(Self {
+            width: structure.base.width,
+            height: structure.base.height,
+            shape: structure.shape,
+            shell: match &structure.slab {
+                Some(slab) => Some(slab.acrylic),
+                None => structure.sleeve,
+            },
+            label_height: structure.slab.as_ref().map(|slab| slab.label_height),
+            silhouette: structure
+                .silhouette
+                .as_ref()
+                .map(|silhouette| silhouette.distances(&structure.base)),
+            base: Texture::image(&structure.base),
+            foil: structure.foil.as_ref().map(Texture::mask),
+            etching: structure.etching.as_ref().map(Texture::mask),
+            wear: structure.wear.as_ref().map(Texture::mask),
+            relief: structure.relief.as_ref().map(Texture::relief),
+            label: structure
+                .slab
+                .as_ref()
+                .map(|slab| Texture::image(&slab.label)),
+            back,
+        })
+    }
+
+    /// Renders the card into RGBA pixels, like a [`Pipeline`] drawing it over
+    /// a transparent `Rgba8UnormSrgb` target of the given dimensions.
+    ///
+    /// [`Pipeline`]: crate::Pipeline
+    pub fn render(
+        &self,
+        configuration: Configuration,
+        rotation: Quaternion,
+        width: u32,
+        height: u32,
+    ) -> Vec<u8> {
+        let mut pixels = vec![0; width as usize * height as usize * 4];
+
+        if pixels.is_empty() {
+            return pixels;
+        }
+
+        let scene = Scene {
+            card: self,
+            geometry: Geometry::new(
+                self.width,
+                self.height,
+                self.shape,
+                self.shell,
+                self.label_height,
+                self.silhouette.as_ref(),
+            ),
+            configuration,
+            rotation,
+            viewport: card::Viewport {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            },
+        };
+
+        let row = width as usize * 4;
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        let rows = (height as usize).div_ceil(threads);
+
+        thread::scope(|scope| {
+            for (i, chunk) in pixels.chunks_mut(row * rows).enumerate() {
+                let scene = &scene;
+
+                let _ = scope.spawn(move || {
+                    for (j, row) in chunk.chunks_exact_mut(row).enumerate() {
+                        let y = (i * rows + j) as u32;
+
+                        for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+                            pixel.copy_from_slice(&scene.pixel(x as u32, y));
+                        }
+                    }
+                });
+            }
+        });
+
+        pixels
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Back {
+    base: Texture,
+    foil: Option<Texture>,
+    etching: Option<Texture>,
+}
+
+struct Scene<'a> {
+    card: &'a Card,
+    geometry: Geometry<'a>,
+    configuration: Configuration,
+    rotation: Quaternion,
+    viewport: card::Viewport,
+}
+
+impl Scene<'_> {
+    // Mirrors `fs_main`, blending over a transparent target
+    fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let n_samples = self.configuration.n_samples.max(1);
+        let max_iterations = self.configuration.max_iterations;
+
+        let position = (x as f32 + 0.5, y as f32 + 0.5);
+        let mut color = [0.0; 4];
+
+        for m in 0..n_samples {
+            for n in 0..n_samples {
+                let o = (
+                    m as f32 / n_samples as f32 - 0.5,
+                    n as f32 / n_samples as f32 - 0.5,
+                );
+
+                let (mut ray_origin, mut ray_direction) =
+                    raymarch::ray(self.viewport, position.0 + o.0, position.1 + o.1);
+                let mut t = -MAX_DISTANCE;
+
+                let mut glare = [0.0; 4];
+                let mut transmission = Vector {
+                    x: 1.0,
+                    y: 1.0,
+                    z: 1.0,
+                };
+
+                if let Some(shell) = self.geometry.shell {
+                    t = self.march(ray_origin, ray_direction, t, Surface::Shell, max_iterations);
+
+                    if t > 2.0 * MAX_DISTANCE {
+                        continue;
+                    }
+
+                    let layer = self.shade_shell(ray_origin, ray_direction, t, shell);
+
+                    glare = layer.glare;
+                    transmission = layer.transmission;
+                    ray_origin = ray_origin + ray_direction * t;
+                    ray_direction = layer.direction;
+                    t = 0.0;
+                }
+
+                t = self.march(ray_origin, ray_direction, t, Surface::Card, max_iterations);
+
+                let mut sample = [0.0; 4];
+
+                if t <= 2.0 * MAX_DISTANCE {
+                    sample = self.shade(ray_origin, ray_direction, t);
+                } else if self.geometry.label.is_some() {
+                    sample = self.shade_label(ray_origin, ray_direction);
+                }
+
+                let glare_rgb = rgb(glare);
+
+                let sample = if sample[3] > 0.0 {
+                    with_alpha(
+                        mul(rgb(sample), transmission) + glare_rgb,
+                        sample[3].max(glare[3]),
+                    )
+                } else {
+                    with_alpha(glare_rgb / glare[3].max(0.001), glare[3])
+                };
+
+                for (channel, value) in color.iter_mut().zip(sample) {
+                    *channel += value;
+                }
+            }
+        }
+
+        let [r, g, b, a] =
+            color.map(|channel| (channel / (n_samples * n_samples) as f32).clamp(0.0, 1.0));
+
+        let encode = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        [
+            encode(mipmap::linear_to_srgb(r * a)),
+            encode(mipmap::linear_to_srgb(g * a)),
+            encode(mipmap::linear_to_srgb(b * a)),
+            encode(a),
+        ]
+    }
+
+    fn march(
+        &self,
+        origin: Vector,
+        direction: Vector,
+        start: f32,
+        surface: Surface,
+        max_iterations: u32,
+    ) -> f32 {
+        raymarch::march(
+            origin,
+            direction,
+            start,
+            self.rotation,
+            &self.geometry,
+            surface,
+            max_iterations,
+        )
+    }
+
+    fn shade(&self, ray_origin: Vector, ray_direction: Vector, t: f32) -> [f32; 4] {
+        let card = self.card;
+        let geometry = &self.geometry;
+        let light = self.configuration.light;
+
+        let hit_rotated = ray_origin + ray_direction * t;
+        let hit = self.rotation.conjugate().rotate(hit_rotated);
+        let normal = geometry.estimate_normal(hit, Surface::Card);
+
+        let mut ctx = SurfaceContext {
+            position: (0.0, 0.0),
+            normal: self.rotation.rotate(normal),
+            view: -ray_direction,
+            light: (light.position - hit_rotated).normalize(),
+            light_strength: light.power / (light.position - hit_rotated).length().powi(2),
+            base: [0.0; 4],
+            foil: 0.0,
+            etch: 0.0,
+            wear: 0.0,
+        };
+
+        if normal.z.abs() > normal.x.abs() && normal.z.abs() > normal.y.abs() {
+            let position = (hit.x - geometry.offset.0, hit.y - geometry.offset.1);
+            let local_uv = (
+                position.0 / (2.0 * geometry.size.0) + 0.5,
+                position.1 / (2.0 * geometry.size.1) + 0.5,
+            );
+            let final_uv = geometry.texture_uv(position.0, position.1);
+            let footprint = self.footprint(
+                ray_direction,
+                hit_rotated,
+                Vector {
+                    x: 0.0,
+                    y: 0.0,
+                    z: normal.z.signum(),
+                },
+            );
+            let texture_scale = (
+                1.0 / (2.0 * geometry.size.0),
+                -1.0 / (2.0 * geometry.size.1),
+            );
+            let gradient = Gradient {
+                dx: (
+                    footprint.dx.0 * texture_scale.0,
+                    footprint.dx.1 * texture_scale.1,
+                ),
+                dy: (
+                    footprint.dy.0 * texture_scale.0,
+                    footprint.dy.1 * texture_scale.1,
+                ),
+            };
+
+            if normal.z < 0.0 {
+                // Front
+                ctx.position = local_uv;
+
+                if let Some(relief) = &card.relief {
+                    let [x, y, z, _] = relief.sample(final_uv, gradient);
+
+                    // The front faces towards -z
+                    ctx.normal = self.rotation.rotate(
+                        Vector {
+                            x: x * 2.0 - 1.0,
+                            y: y * 2.0 - 1.0,
+                            z: -(z * 2.0 - 1.0),
+                        }
+                        .normalize(),
+                    );
+                }
+
+                if let Some(wear) = &card.wear {
+                    ctx.wear = wear.sample(final_uv, gradient)[0];
+                }
+
+                ctx.base = card.base.sample(final_uv, gradient);
+
+                if let Some(foil) = &card.foil {
+                    ctx.foil = foil.sample(final_uv, gradient)[0];
+                }
+
+                if let Some(etching) = &card.etching {
+                    ctx.etch = etching.sample(final_uv, gradient)[0];
+                }
+            } else {
+                // Back
+                let back_uv = (1.0 - final_uv.0, final_uv.1);
+                let back_gradient = Gradient {
+                    dx: (-gradient.dx.0, gradient.dx.1),
+                    dy: (-gradient.dy.0, gradient.dy.1),
+                };
+
+                ctx.position = (1.0 - local_uv.0, local_uv.1);
+                ctx.base = card.back.base.sample(back_uv, back_gradient);
+
+                if let Some(foil) = &card.back.foil {
+                    ctx.foil = foil.sample(back_uv, back_gradient)[0];
+                }
+
+                if let Some(etching) = &card.back.etching {
+                    ctx.etch = etching.sample(back_uv, back_gradient)[0];
+                }
+            }
+        } else {
+            // Side edge
+            ctx.base = [0.5, 0.5, 0.5, ctx.normal.z.abs()];
+        }
+
+        holo(&ctx)
+    }
+
+    // Displacement on a flat face of the card between neighboring pixels,
+    // following the ray differentials of the camera
+    fn footprint(&self, ray_direction: Vector, hit_rotated: Vector, normal: Vector) -> Gradient {
+        let pixel = 2.0 / self.viewport.height as f32;
+        let d = ray_direction;
+        let n = self.rotation.rotate(normal);
+        let t = (hit_rotated
+            - Vector {
+                x: 0.0,
+                y: 0.0,
+                z: -MAX_DISTANCE,
+            })
+        .length();
+
+        // Grazing angles blow up the footprint, which only picks the coarsest level
+        let cosine = d.dot(n);
+        let denominator = if cosine >= 0.0 { 1.0 } else { -1.0 } * cosine.abs().max(0.01);
+
+        let dd_x = (Vector::X * pixel - d * d.x * pixel) * d.z / 3.0;
+        let dd_y = (Vector::Y * pixel - d * d.y * pixel) * d.z / 3.0;
+
+        let dp_x = (dd_x - d * dd_x.dot(n) / denominator) * t;
+        let dp_y = (dd_y - d * dd_y.dot(n) / denominator) * t;
+
+        let inverse = self.rotation.conjugate();
+        let dp_x = inverse.rotate(dp_x);
+        let dp_y = inverse.rotate(dp_y);
+
+        Gradient {
+            dx: (dp_x.x, dp_x.y),
+            dy: (dp_y.x, dp_y.y),
+        }
+    }
+
+    // The label of a slab lies on the same plane as the card
+    fn shade_label(&self, ray_origin: Vector, ray_direction: Vector) -> [f32; 4] {
+        let Some(label) = &self.card.label else {
+            return [0.0; 4];
+        };
+
+        let geometry = &self.geometry;
+        let shell = geometry.shell.unwrap_or(Sleeve::NONE);
+        let light = self.configuration.light;
+        let inverse = self.rotation.conjugate();
+        let origin = inverse.rotate(ray_origin);
+        let direction = inverse.rotate(ray_direction);
+
+        if direction.z.abs() < 0.00001 {
+            return [0.0; 4];
+        }
+
+        let t = -origin.z / direction.z;
+
+        if t < 0.0 {
+            return [0.0; 4];
+        }
+
+        let width = 2.0 * geometry.size.0;
+        let margin = shell.margin * width;
+        let height = geometry.label.unwrap_or_default() * width;
+
+        let hit = origin + direction * t;
+        let center = (
+            geometry.offset.0,
+            geometry.offset.1 + geometry.size.1 + margin + height / 2.0,
+        );
+        let uv = ((hit.x - center.0) / width, (hit.y - center.1) / height);
+
+        if uv.0.abs() > 0.5 || uv.1.abs() > 0.5 {
+            return [0.0; 4];
+        }
+
+        let hit_rotated = ray_origin + ray_direction * t;
+        let normal = Vector {
+            x: 0.0,
+            y: 0.0,
+            z: -direction.z.signum(),
+        };
+        let footprint = self.footprint(ray_direction, hit_rotated, normal);
+        let scale = (1.0 / width, -1.0 / height);
+
+        let sample = label.sample(
+            (uv.0 + 0.5, 0.5 - uv.1),
+            Gradient {
+                dx: (footprint.dx.0 * scale.0, footprint.dx.1 * scale.1),
+                dy: (footprint.dy.0 * scale.0, footprint.dy.1 * scale.1),
+            },
+        );
+
+        let n = self.rotation.rotate(normal);
+        let l = (light.position - hit_rotated).normalize();
+        let light_strength = light.power / (light.position - hit_rotated).length().powi(2);
+        let diffusion = n.dot(l).clamp(0.0, 1.0) * light_strength;
+
+        with_alpha(rgb(sample) * (0.2 + diffusion), sample[3])
+    }
+
+    // A transparent plastic shell around the card
+    fn shade_shell(
+        &self,
+        ray_origin: Vector,
+        ray_direction: Vector,
+        t: f32,
+        shell: Sleeve,
+    ) -> Layer {
+        let light = self.configuration.light;
+        let refraction = shell.refraction;
+        let gloss = shell.gloss;
+        let tint = shell.tint;
+
+        let hit_rotated = ray_origin + ray_direction * t;
+        let hit = self.rotation.conjugate().rotate(hit_rotated);
+        let normal = self.geometry.estimate_normal(hit, Surface::Shell);
+        let n = self.rotation.rotate(normal);
+        let v = -ray_direction;
+        let l = (light.position - hit_rotated).normalize();
+        let h = (l + v).normalize();
+        let light_strength = light.power / (light.position - hit_rotated).length().powi(2);
+
+        // Schlick's approximation of the Fresnel term
+        let f0 = ((refraction - 1.0) / (refraction + 1.0)).powi(2);
+        let fresnel = f0 + (1.0 - f0) * (1.0 - n.dot(v).clamp(0.0, 1.0)).powi(5);
+
+        let r = reflect(ray_direction, n);
+        let environment = mix(0.02, 0.3, 0.5 + 0.5 * r.y);
+        let specular =
+            n.dot(h).clamp(0.0, 1.0).powf(mix(16.0, 512.0, gloss)) * light_strength * gloss;
+
+        // The edges of the shell look through more plastic
+        let edge = (1.0 - normal.z.abs()) * tint[3];
+        let reflection = environment * fresnel * gloss + specular;
+        let tint = Vector {
+            x: tint[0],
+            y: tint[1],
+            z: tint[2],
+        };
+
+        Layer {
+            glare: with_alpha(
+                tint * edge
+                    + Vector {
+                        x: reflection,
+                        y: reflection,
+                        z: reflection,
+                    },
+                (fresnel + specular + edge).clamp(0.0, 1.0),
+            ),
+            transmission: Vector {
+                x: mix(1.0, tint.x, edge),
+                y: mix(1.0, tint.y, edge),
+                z: mix(1.0, tint.z, edge),
+            } * (1.0 - fresnel),
+            direction: raymarch::refract(ray_direction, n, 1.0 / refraction),
+        }
+    }
+}
+
+struct Layer {
+    glare: [f32; 4],
+    transmission: Vector,
+    direction: Vector,
+}
+
+// Mirrors the `SurfaceContext` of the shader, without the fields that the
+// built-in material ignores
+struct SurfaceContext {
+    position: (f32, f32),
+    normal: Vector,
+    view: Vector,
+    light: Vector,
+    light_strength: f32,
+    base: [f32; 4],
+    foil: f32,
+    etch: f32,
+    wear: f32,
+}
+
+// The built-in holographic material
+fn holo(ctx: &SurfaceContext) -> [f32; 4] {
+    let sample = rgb(ctx.base);
+    let n = ctx.normal;
+    let v = ctx.view;
+    let l = ctx.light;
+    let light_strength = ctx.light_strength;
+    let light_angle = n.dot((l + v).normalize()).clamp(0.0, 1.0);
+    let wear = ctx.wear;
+    let etch = ctx.etch;
+    let foil = ctx.foil;
+
+    let mut specular_color = 1.0;
+    let mut foil_color = Vector::default();
+
+    if foil > 0.1 {
+        let properties = foil_properties(ctx);
+        let purity = properties.purity;
+
+        // Worn foil is duller and scatters light in a wider lobe
+        let strength =
+            light_angle.powf(mix(128.0, 24.0, wear)) * (1.0 - etch * 0.3) * (1.0 - wear * 0.5);
+        let angle = n.dot(l).clamp(0.0, 1.0);
+
+        foil_color = (sample + iridescence(angle, 1000.0, 5.0) * 0.4) * strength * foil;
+        specular_color = 0.0;
+
+        // Foil flakes
+        if has_flakes(&properties) {
+            let cell = (
+                (ctx.position.0 * FLAKE_SIZE).floor(),
+                (ctx.position.1 * FLAKE_SIZE).floor(),
+            );
+
+            // Procedural flake mask
+            let flake = hash(cell);
+            let flake_mask = smoothstep(FLAKE_REDUCTION, 1.0, flake);
+
+            // Perturbed flake normal
+            let angle_offset = (hash((flake, flake + 3.0)) - 0.5) * 2.0;
+            let perturbed_normal = (n + Vector {
+                x: angle_offset,
+                y: 0.0,
+                z: angle_offset,
+            })
+            .normalize();
+
+            // Reflection for sparkle
+            let pr = reflect(-v, perturbed_normal);
+
+            // Dynamic flicker factor (only brightens, never darkens)
+            let flake_phase = hash((
+                cell.0 + (pr.x * 15.0).floor(),
+                cell.1 + (pr.y * 15.0).floor(),
+            ));
+            let phase_mod = mix(1.0, 1.8, flake_phase);
+
+            let flake_spec = (perturbed_normal.dot(v) * 0.5 + 0.5)
+                .clamp(0.0, 1.0)
+                .powf(128.0);
+            let flake_iri = iridescence(perturbed_normal.dot(v), 10000.0, 0.0);
+
+            let flake_intensity =
+                (flake_mask * purity * flake_spec * phase_mod * (1.0 - wear)).clamp(0.0, 1.0);
+
+            foil_color = foil_color + flake_iri * flake_intensity;
+        }
+
+        foil_color = foil_color * light_strength;
+    }
+
+    let ambient = 0.2;
+    let diffusion = n.dot(l).clamp(0.0, 1.0) * light_strength;
+    let specular = light_angle.powf(mix(16.0, 4.0, wear)) * light_strength * 0.02;
+
+    // Scratches catch the light, even on top of foil
+    let scratches = wear * light_angle.powi(2) * light_strength * 0.4;
+    let highlight = specular_color * specular + scratches;
+
+    with_alpha(
+        sample * (ambient + diffusion)
+            + Vector {
+                x: highlight,
+                y: highlight,
+                z: highlight,
+            }
+            + foil_color,
+        ctx.base[3],
+    )
+}
+
+struct FoilProperties {
+    luminance: f32,
+    chroma: f32,
+    purity: f32,
+}
+
+fn foil_properties(ctx: &SurfaceContext) -> FoilProperties {
+    let [r, g, b, _] = ctx.base;
+    let max_channel = r.max(g).max(b);
+
+    FoilProperties {
+        luminance: luminance(rgb(ctx.base)),
+        chroma: (max_channel - r.min(g).min(b)) / max_channel.max(0.0001),
+        purity: (ctx.foil - 4.0 * ctx.etch).clamp(0.0, 1.0),
+    }
+}
+
+// Only pure, colorful and bright foil sparkles
+fn has_flakes(properties: &FoilProperties) -> bool {
+    properties.purity > 0.2 && properties.chroma > 0.3 && properties.luminance > 0.1
+}
+
+fn iridescence(angle: f32, range: f32, offset: f32) -> Vector {
+    let thickness = 100.0 + range * (1.0 - angle);
+
+    // The same approximation of tau as the shader
+    #[allow(clippy::approx_constant)]
+    let phase = 6.28318 * thickness * 0.01 + offset;
+
+    Vector {
+        x: 0.4 + 0.5 * phase.sin(),
+        y: 0.4 + 0.5 * (phase + 2.094).sin(),
+        z: 0.4 + 0.5 * (phase + 4.188).sin(),
+    }
+}
+
+fn hash(p: (f32, f32)) -> f32 {
+    fract((p.0 * 127.1 + p.1 * 311.7).sin() * 43758.547)
+}
+
+fn luminance(color: Vector) -> f32 {
+    color.dot(Vector {
+        x: 0.2126,
+        y: 0.7152,
+        z: 0.0722,
+    })
+}
+
+fn reflect(incident: Vector, normal: Vector) -> Vector {
+    incident - normal * (2.0 * normal.dot(incident))
+}
+
+fn smoothstep(low: f32, high: f32, x: f32) -> f32 {
+    let t = ((x - low) / (high - low)).clamp(0.0, 1.0);
+
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a * (1.0 - t) + b * t
+}
+
+// Unlike `f32::fract`, negative numbers wrap around like in WGSL
+fn fract(x: f32) -> f32 {
+    x - x.floor()
+}
+
+// Colors are vectors of their red, green and blue channels
+fn rgb(color: [f32; 4]) -> Vector {
+    Vector {
+        x: color[0],
+        y: color[1],
+        z: color[2],
+    }
+}
+
+fn with_alpha(color: Vector, alpha: f32) -> [f32; 4] {
+    [color.x, color.y, color.z, alpha]
+}
+
+fn mul(a: Vector, b: Vector) -> Vector {
+    Vector {
+        x: a.x * b.x,
+        y: a.y * b.y,
+        z: a.z * b.z,
+    }
+}
+
+// Derivatives of texture coordinates between neighboring pixels
+#[derive(Debug, Clone, Copy)]
+struct Gradient {
+    dx: (f32, f32),
+    dy: (f32, f32),
+}
+
+/// A texture with its whole mip chain decoded into linear values.
+#[derive(Debug, Clone)]
+struct Texture {
+    channels: usize,
+    levels: Vec<Level>,
+}
+
+#[derive(Debug, Clone)]
+struct Level {
+    width: usize,
+    height: usize,
+    texels: Vec<f32>,
+}
+
+impl Texture {
+    fn image(image: &Image) -> Self {
+        Self::new(
+            image.width,
+            image.height,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            &image.decompressed(),
+        )
+    }
+
+    fn mask(mask: &Mask) -> Self {
+        Self::new(
+            mask.width,
+            mask.height,
+            wgpu::TextureFormat::R8Unorm,
+            &mask.decompressed(),
+        )
+    }
+
+    // Normals are not colors!
+    fn relief(relief: &Relief) -> Self {
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+
+        match relief {
+            Relief::Height { map, strength } => Self::new(
+                map.width,
+                map.height,
+                format,
+                &card::normal_map(map, *strength),
+            ),
+            Relief::Normal(image) => {
+                let normals = match image.compression {
+                    Some(compression) => {
+                        compression.decompress(image.width, image.height, false, &image.rgba)
+                    }
+                    None => image.rgba.to_vec(),
+                };
+
+                Self::new(image.width, image.height, format, &normals)
+            }
+        }
+    }
+
+    fn new(width: u32, height: u32, format: wgpu::TextureFormat, data: &[u8]) -> Self {
+        let channels = format.block_copy_size(None).unwrap_or(4) as usize;
+        let is_srgb = format.is_srgb();
+        let chain = mipmap::chain(width, height, format, data);
+
+        let mut offset = 0;
+        let levels = (0..mipmap::count(width, height))
+            .map(|level| {
+                let width = (width >> level).max(1) as usize;
+                let height = (height >> level).max(1) as usize;
+                let size = width * height * channels;
+
+                let texels = chain[offset..offset + size]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &value)| {
+                        if is_srgb && i % channels < 3 {
+                            mipmap::SRGB_TO_LINEAR[value as usize]
+                        } else {
+                            f32::from(value) / 255.0
+                        }
+                    })
+                    .collect();
+
+                offset += size;
+
+                Level {
+                    width,
+                    height,
+                    texels,
+                }
+            })
+            .collect();
+
+        Self { channels, levels }
+    }
+
+    // Trilinear filtering, like the sampler of the shader without anisotropy
+    fn sample(&self, uv: (f32, f32), gradient: Gradient) -> [f32; 4] {
+        let first = &self.levels[0];
+        let (width, height) = (first.width as f32, first.height as f32);
+
+        let footprint = (gradient.dx.0 * width)
+            .hypot(gradient.dx.1 * height)
+            .max((gradient.dy.0 * width).hypot(gradient.dy.1 * height));
+
+        let lod = footprint.log2().clamp(0.0, (self.levels.len() - 1) as f32);
+        let level = lod.floor() as usize;
+        let blend = lod - level as f32;
+
+        let lower = self.sample_level(level, uv);
+
+        if blend == 0.0 {
+            return lower;
+        }
+
+        let upper = self.sample_level(level + 1, uv);
+
+        std::array::from_fn(|i| mix(lower[i], upper[i], blend))
+    }
+
+    // Bilinear filtering with clamped edges
+    fn sample_level(&self, level: usize, (u, v): (f32, f32)) -> [f32; 4] {
+        let level = &self.levels[level];
+
+        let x = (u * level.width as f32 - 0.5).clamp(0.0, (level.width - 1) as f32);
+        let y = (v * level.height as f32 - 0.5).clamp(0.0, (level.height - 1) as f32);
+
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = (
+            (x0 + 1).min(level.width - 1),
+            (y0 + 1).min(level.height - 1),
+        );
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+        let texel = |x: usize, y: usize| {
+            let i = (y * level.width + x) * self.channels;
+            let mut texel = [0.0, 0.0, 0.0, 1.0];
+
+            texel[..self.channels].copy_from_slice(&level.texels[i..i + self.channels]);
+            texel
+        };
+
+        let (a, b, c, d) = (texel(x0, y0), texel(x1, y0), texel(x0, y1), texel(x1, y1));
+
+        std::array::from_fn(|i| mix(mix(a[i], b[i], fx), mix(c[i], d[i], fx), fy))
+    }
+}
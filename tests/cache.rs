@@ -0,0 +1,220 @@
+//! Uploads small cards to a software adapter, like llvmpipe or lavapipe, and
+//! checks how a [`CardCache`] spends its budget.
+//!
+//! The tests only run with the `software-tests` feature, and fail if no
+//! software adapter is available.
+mod common;
+
+use holofoil::card;
+use holofoil::{CardCache, Pipeline, Quaternion};
+
+use std::sync::Arc;
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn evicts_least_recently_used() {
+    let (device, queue, pipeline) = setup();
+    let size = card_memory(&device, &queue, &pipeline);
+
+    let mut cache = CardCache::new(2 * size);
+
+    for key in ["a", "b", "c"] {
+        cache.insert(key, structure());
+    }
+
+    for key in ["a", "b", "a", "c"] {
+        cache.begin_frame();
+
+        assert!(
+            cache
+                .prepare(&pipeline, &device, &queue, &key, parameters())
+                .unwrap()
+        );
+    }
+
+    assert!(cache.get(&"a").is_some());
+    assert!(cache.get(&"b").is_none());
+    assert!(cache.get(&"c").is_some());
+    assert_eq!(cache.memory(), 2 * size);
+
+    // Evicted cards are uploaded again, evicting the next one in line
+    cache.begin_frame();
+
+    assert!(
+        cache
+            .prepare(&pipeline, &device, &queue, &"b", parameters())
+            .unwrap()
+    );
+
+    assert!(cache.get(&"a").is_none());
+    assert!(cache.get(&"b").is_some());
+    assert!(cache.get(&"c").is_some());
+    assert_eq!(cache.memory(), 2 * size);
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn set_budget_evicts() {
+    let (device, queue, pipeline) = setup();
+    let size = card_memory(&device, &queue, &pipeline);
+
+    let mut cache = CardCache::new(u64::MAX);
+
+    for key in ["a", "b", "c"] {
+        cache.insert(key, structure());
+        cache.begin_frame();
+
+        assert!(
+            cache
+                .prepare(&pipeline, &device, &queue, &key, parameters())
+                .unwrap()
+        );
+    }
+
+    assert_eq!(cache.memory(), 3 * size);
+
+    cache.set_budget(2 * size);
+
+    assert_eq!(cache.budget(), 2 * size);
+    assert_eq!(cache.memory(), 2 * size);
+    assert!(cache.get(&"a").is_none());
+
+    // The cards of the current frame are kept, even over the budget
+    cache.set_budget(0);
+
+    assert_eq!(cache.memory(), size);
+    assert!(cache.get(&"b").is_none());
+    assert!(cache.get(&"c").is_some());
+
+    // Structures outlive their cards
+    assert!(cache.structure(&"a").is_some());
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn keeps_cards_of_current_frame() {
+    let (device, queue, pipeline) = setup();
+    let size = card_memory(&device, &queue, &pipeline);
+
+    let mut cache = CardCache::new(2 * size);
+
+    for key in ["a", "b", "c", "d"] {
+        cache.insert(key, structure());
+    }
+
+    cache.begin_frame();
+
+    for key in ["a", "b", "c", "d"] {
+        assert!(
+            cache
+                .prepare(&pipeline, &device, &queue, &key, parameters())
+                .unwrap()
+        );
+    }
+
+    // Every card of the frame renders, over the budget
+    for key in ["a", "b", "c", "d"] {
+        assert!(cache.get(&key).is_some());
+    }
+
+    assert_eq!(cache.memory(), 4 * size);
+
+    // The next frame evicts the least recently used cards of the last one
+    cache.begin_frame();
+
+    assert!(
+        cache
+            .prepare(&pipeline, &device, &queue, &"a", parameters())
+            .unwrap()
+    );
+
+    assert!(cache.get(&"a").is_some());
+    assert!(cache.get(&"b").is_none());
+    assert!(cache.get(&"c").is_none());
+    assert!(cache.get(&"d").is_some());
+    assert_eq!(cache.memory(), 2 * size);
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn memory_follows_insert_and_remove() {
+    let (device, queue, pipeline) = setup();
+    let size = card_memory(&device, &queue, &pipeline);
+
+    let mut cache = CardCache::new(u64::MAX);
+
+    cache.insert("a", structure());
+    cache.insert("b", structure());
+
+    // Nothing is uploaded until cards are prepared
+    assert_eq!(cache.memory(), 0);
+
+    for key in ["a", "b"] {
+        assert!(
+            cache
+                .prepare(&pipeline, &device, &queue, &key, parameters())
+                .unwrap()
+        );
+    }
+
+    assert_eq!(cache.memory(), 2 * size);
+
+    // Replacing a card frees the old one
+    cache.insert("a", structure());
+
+    assert_eq!(cache.memory(), size);
+    assert!(cache.get(&"a").is_none());
+
+    assert!(cache.remove(&"b").is_some());
+    assert!(cache.remove(&"b").is_none());
+    assert_eq!(cache.memory(), 0);
+
+    assert!(
+        !cache
+            .prepare(&pipeline, &device, &queue, &"b", parameters())
+            .unwrap()
+    );
+}
+
+fn setup() -> (wgpu::Device, wgpu::Queue, Pipeline) {
+    let (device, queue) = common::device();
+    let pipeline = common::pipeline(&device, &queue, common::blank(16, 22));
+
+    (device, queue, pipeline)
+}
+
+// Every card of the tests takes the same memory
+fn card_memory(device: &wgpu::Device, queue: &wgpu::Queue, pipeline: &Pipeline) -> u64 {
+    pipeline
+        .upload(device, queue, &structure())
+        .unwrap()
+        .memory()
+}
+
+fn parameters() -> card::Parameters {
+    card::Parameters {
+        viewport: card::Viewport {
+            x: 0,
+            y: 0,
+            width: 64,
+            height: 64,
+        },
+        rotation: Quaternion::default(),
+    }
+}
+
+fn structure() -> Arc<card::Structure> {
+    Arc::new(card::Structure::new(common::blank(16, 22)))
+}
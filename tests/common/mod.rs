@@ -0,0 +1,239 @@
+//! The software adapter and the cards shared by the integration tests.
+#![allow(dead_code)]
+
+use holofoil::{Bytes, Pipeline, Settings, card};
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Requests a device from a software adapter, like llvmpipe or lavapipe.
+pub fn device() -> (wgpu::Device, wgpu::Queue) {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::all(),
+        ..wgpu::InstanceDescriptor::default()
+    });
+
+    let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+        force_fallback_adapter: true,
+        ..wgpu::RequestAdapterOptions::default()
+    }))
+    .expect("A software adapter, like llvmpipe or lavapipe, is needed");
+
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+        label: Some("holofoil tests"),
+        required_limits: adapter.limits(),
+        ..wgpu::DeviceDescriptor::default()
+    }))
+    .unwrap()
+}
+
+/// Creates a [`Pipeline`] rendering to sRGB textures with the given back.
+///
+/// Anisotropic filtering is optional, and implemented differently, so it is
+/// disabled.
+pub fn pipeline(device: &wgpu::Device, queue: &wgpu::Queue, back: card::Image) -> Pipeline {
+    Pipeline::with_settings(
+        device,
+        queue,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        back,
+        Settings {
+            anisotropy: 1,
+            ..Settings::default()
+        },
+    )
+    .unwrap()
+}
+
+/// Prepares and renders a card into a transparent sRGB texture the size of
+/// its [`card::Viewport`], and reads its pixels back.
+pub fn render(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    pipeline: &Pipeline,
+    card: &mut holofoil::Card,
+    parameters: card::Parameters,
+) -> Vec<u8> {
+    let card::Viewport { width, height, .. } = parameters.viewport;
+
+    card.prepare(queue, parameters);
+
+    draw(device, queue, width, height, |pass| {
+        pipeline.render(pass, card)
+    })
+}
+
+/// Draws into a transparent sRGB texture of the given size, and reads its
+/// pixels back.
+pub fn draw(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    width: u32,
+    height: u32,
+    draw: impl FnOnce(&mut wgpu::RenderPass<'_>),
+) -> Vec<u8> {
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8UnormSrgb,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+
+    // Rows must be aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`
+    assert_eq!(width * 4 % wgpu::COPY_BYTES_PER_ROW_ALIGNMENT, 0);
+
+    let output = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: u64::from(width * height * 4),
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let view = target.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+
+    {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            ..wgpu::RenderPassDescriptor::default()
+        });
+
+        draw(&mut pass);
+    }
+
+    encoder.copy_texture_to_buffer(
+        target.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &output,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(width * 4),
+                rows_per_image: None,
+            },
+        },
+        target.size(),
+    );
+
+    let _ = queue.submit([encoder.finish()]);
+
+    output.slice(..).map_async(wgpu::MapMode::Read, |_| {});
+    device
+        .poll(wgpu::PollType::wait_indefinitely())
+        .expect("Render card");
+
+    output.slice(..).get_mapped_range().to_vec()
+}
+
+pub fn umbreon() -> card::Structure {
+    card::Structure {
+        etching: Some(load_mask("sv8-5_en_161_std.etch.png")),
+        ..structure("sv8-5_en_161_std.png", "sv8-5_en_161_std.foil.png")
+    }
+}
+
+pub fn bellibolt() -> card::Structure {
+    card::Structure {
+        etching: Some(load_mask("sv9_en_188_std.etch.png")),
+        ..structure("sv9_en_188_std.png", "sv9_en_188_std.foil.png")
+    }
+}
+
+fn structure(base: &str, foil: &str) -> card::Structure {
+    card::Structure {
+        foil: Some(load_mask(foil)),
+        ..card::Structure::new(load_image(base))
+    }
+}
+
+/// A gray image, quick to upload.
+pub fn blank(width: u32, height: u32) -> card::Image {
+    card::Image {
+        rgba: Bytes::from(vec![128; (width * height * 4) as usize]),
+        width,
+        height,
+        compression: None,
+    }
+}
+
+pub fn load_image(name: &str) -> card::Image {
+    let (rgba, width, height) = decode(&asset(name), true);
+    let (rgba, width) = crop(&rgba, width, 4);
+
+    card::Image {
+        rgba: Bytes::from(rgba),
+        width,
+        height,
+        compression: None,
+    }
+}
+
+pub fn load_mask(name: &str) -> card::Mask {
+    let (pixels, width, height) = decode(&asset(name), false);
+    let (pixels, width) = crop(&pixels, width, 1);
+
+    card::Mask {
+        pixels: Bytes::from(pixels),
+        width,
+        height,
+        compression: None,
+    }
+}
+
+fn asset(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("examples/showcase/assets")
+        .join(name);
+
+    fs::read(&path).unwrap_or_else(|error| panic!("{}: {error}", path.display()))
+}
+
+/// Decodes a PNG into its pixels and size, expanding it to RGBA if `alpha`
+/// is set.
+pub fn decode(bytes: &[u8], alpha: bool) -> (Vec<u8>, u32, u32) {
+    let mut decoder = png::Decoder::new(io::Cursor::new(bytes));
+
+    if alpha {
+        decoder.set_transformations(png::Transformations::ALPHA);
+    }
+
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+
+    let metadata = reader.next_frame(&mut pixels).unwrap();
+    pixels.truncate(metadata.buffer_size());
+
+    (pixels, metadata.width, metadata.height)
+}
+
+// The scans in the assets are padded into squares
+fn crop(pixels: &[u8], width: u32, channels: usize) -> (Vec<u8>, u32) {
+    let cropped = width * 733 / 1024;
+    let offset = (width - cropped) as usize / 2 * channels;
+    let row = width as usize * channels;
+
+    let pixels = pixels
+        .chunks_exact(row)
+        .flat_map(|row| &row[offset..offset + cropped as usize * channels])
+        .copied()
+        .collect();
+
+    (pixels, cropped)
+}
@@ -0,0 +1,496 @@
+//! Renders the example cards on a software adapter, like llvmpipe or
+//! lavapipe, and compares them with the reference images in `tests/golden`.
+//! The software renderer and hit testing are checked against the same
+//! references.
+//!
+//! The tests that need a software adapter only run with the `software-tests`
+//! feature, and fail if none is available. Run them with `HOLOFOIL_BLESS=1` to
+//! replace the references after an intended change of the look.
+mod common;
+
+use common::{bellibolt, decode, load_image, umbreon};
+
+use holofoil::{Bytes, Configuration, Light, Quaternion, Vector};
+use holofoil::{card, software};
+
+use std::fs;
+use std::path::PathBuf;
+
+const SIZE: u32 = 256;
+
+// Software adapters may disagree this much on a channel, since they
+// approximate trigonometry differently, in a few pixels
+const GPU: Tolerance = Tolerance {
+    name: "gpu",
+    threshold: 4,
+    max_different: 0.001,
+    max_difference: 255,
+    bless: true,
+};
+
+// The software renderer rounds differently from the shader, which shows on
+// the edges and in glints
+const SOFTWARE: Tolerance = Tolerance {
+    name: "software",
+    threshold: 2,
+    max_different: 0.01,
+    max_difference: 40,
+    bless: false,
+};
+
+// Cut-out edges, steep reliefs and the dense glints of a foil seen from
+// behind drift further
+const SOFTWARE_LOOSE: Tolerance = Tolerance {
+    name: "software",
+    threshold: 2,
+    max_different: 0.03,
+    max_difference: 100,
+    bless: false,
+};
+
+// Supersampled edges are partially covered even when the centers of their
+// pixels miss the card
+const MAX_MISSED: usize = 250;
+
+#[derive(Clone, Copy)]
+struct Tolerance {
+    name: &'static str,
+    threshold: u8,
+    max_different: f32,
+    max_difference: u8,
+    bless: bool,
+}
+
+struct Case {
+    name: &'static str,
+    card: fn() -> card::Structure,
+    rotation: (f32, f32),
+    configuration: Configuration,
+    software: Tolerance,
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn golden() {
+    let (device, queue) = common::device();
+    let mut pipeline = common::pipeline(&device, &queue, load_image("pokemon_tcg_back.png"));
+
+    let failures: Vec<String> = cases()
+        .iter()
+        .filter_map(|case| {
+            let mut card = pipeline.upload(&device, &queue, &(case.card)()).unwrap();
+
+            pipeline
+                .configure(&device, &queue, case.configuration)
+                .unwrap();
+
+            let parameters = card::Parameters {
+                viewport: viewport(),
+                rotation: rotation(case.rotation),
+            };
+
+            let pixels = common::render(&device, &queue, &pipeline, &mut card, parameters);
+
+            compare(case.name, &pixels, GPU).err()
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn software() {
+    let back = load_image("pokemon_tcg_back.png");
+
+    let failures: Vec<String> = cases()
+        .iter()
+        .filter_map(|case| {
+            let card = software::Card::new(&(case.card)(), &back).unwrap();
+            let pixels = card.render(case.configuration, rotation(case.rotation), SIZE, SIZE);
+
+            compare(case.name, &pixels, case.software).err()
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn hit_test() {
+    let (device, queue) = common::device();
+    let pipeline = common::pipeline(&device, &queue, load_image("pokemon_tcg_back.png"));
+
+    let failures: Vec<String> = cases()
+        .iter()
+        .filter_map(|case| {
+            let structure = (case.card)();
+            let card = pipeline.upload(&device, &queue, &structure).unwrap();
+
+            // Sleeves and slabs cover more than the card, but only the card
+            // is hit
+            let shell = structure.sleeve.is_some() || structure.slab.is_some();
+
+            let parameters = card::Parameters {
+                viewport: viewport(),
+                rotation: rotation(case.rotation),
+            };
+
+            let expected = match reference(case.name) {
+                Ok(expected) => expected,
+                Err(error) => return Some(error),
+            };
+
+            // Pixels are hit on their centers
+            let different = expected
+                .chunks_exact(4)
+                .enumerate()
+                .filter(|(i, pixel)| {
+                    let point = card::Point {
+                        x: (*i as u32 % SIZE) as f32 + 0.5,
+                        y: (*i as u32 / SIZE) as f32 + 0.5,
+                    };
+
+                    let hit = card.hit_test(parameters, point).is_some();
+                    let covered = pixel[3] > 0;
+
+                    if shell {
+                        hit && !covered
+                    } else {
+                        hit != covered
+                    }
+                })
+                .count();
+
+            (different > MAX_MISSED).then(|| {
+                format!(
+                    "{}: {different} pixels disagree with the coverage of the reference",
+                    case.name
+                )
+            })
+        })
+        .collect();
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+fn cases() -> Vec<Case> {
+    let side_light = Configuration {
+        light: Light {
+            position: Vector {
+                x: -12.0,
+                y: 2.0,
+                z: -10.0,
+            },
+            power: 250.0,
+        },
+        ..Configuration::default()
+    };
+
+    vec![
+        Case {
+            name: "umbreon_front",
+            card: umbreon,
+            rotation: (0.3, 0.4),
+            configuration: Configuration::default(),
+            software: SOFTWARE,
+        },
+        Case {
+            name: "umbreon_side_light",
+            card: umbreon,
+            rotation: (-0.4, -0.6),
+            configuration: side_light,
+            software: SOFTWARE,
+        },
+        Case {
+            name: "umbreon_back",
+            card: umbreon,
+            rotation: (0.2, 2.8),
+            configuration: Configuration::default(),
+            software: SOFTWARE,
+        },
+        Case {
+            name: "bellibolt_front",
+            card: bellibolt,
+            rotation: (0.3, 0.4),
+            configuration: Configuration::default(),
+            software: SOFTWARE,
+        },
+        Case {
+            name: "bellibolt_grazing",
+            card: bellibolt,
+            rotation: (0.1, 1.2),
+            configuration: side_light,
+            software: SOFTWARE,
+        },
+        Case {
+            name: "bellibolt_single_sample",
+            card: bellibolt,
+            rotation: (-0.2, 0.3),
+            configuration: Configuration {
+                n_samples: 1,
+                ..Configuration::default()
+            },
+            software: SOFTWARE,
+        },
+        Case {
+            name: "umbreon_die_cut",
+            card: || card::Structure {
+                silhouette: Some(card::Silhouette::Alpha),
+                ..die_cut(umbreon())
+            },
+            rotation: (0.3, 0.4),
+            configuration: Configuration::default(),
+            software: SOFTWARE_LOOSE,
+        },
+        Case {
+            name: "bellibolt_circle",
+            card: || card::Structure {
+                shape: card::Shape::Circle { ridges: 24 },
+                ..bellibolt()
+            },
+            rotation: (-0.3, 0.5),
+            configuration: Configuration::default(),
+            software: SOFTWARE,
+        },
+        Case {
+            name: "umbreon_hexagon",
+            card: || card::Structure {
+                shape: card::Shape::Polygon {
+                    sides: 6,
+                    radius: 0.1,
+                },
+                ..umbreon()
+            },
+            rotation: (0.2, -0.4),
+            configuration: side_light,
+            software: SOFTWARE,
+        },
+        Case {
+            name: "bellibolt_toploader",
+            card: || card::Structure {
+                sleeve: Some(card::Sleeve::TOPLOADER),
+                ..bellibolt()
+            },
+            rotation: (0.3, 0.4),
+            configuration: Configuration::default(),
+            software: SOFTWARE,
+        },
+        Case {
+            name: "umbreon_slab",
+            card: || card::Structure {
+                slab: Some(card::Slab::new(label())),
+                ..umbreon()
+            },
+            rotation: (-0.2, 0.5),
+            configuration: Configuration::default(),
+            software: SOFTWARE,
+        },
+        Case {
+            name: "bellibolt_wear",
+            card: || card::Structure {
+                wear: Some(scratches()),
+                ..bellibolt()
+            },
+            rotation: (0.1, 0.6),
+            configuration: side_light,
+            software: SOFTWARE,
+        },
+        Case {
+            name: "umbreon_relief",
+            card: || {
+                let structure = umbreon();
+
+                card::Structure {
+                    relief: Some(card::Relief::Height {
+                        map: structure.etching.clone().unwrap(),
+                        strength: 3.0,
+                    }),
+                    ..structure
+                }
+            },
+            rotation: (0.1, 1.0),
+            configuration: side_light,
+            software: SOFTWARE_LOOSE,
+        },
+        Case {
+            name: "umbreon_own_back",
+            card: || {
+                let bellibolt = bellibolt();
+
+                card::Structure {
+                    back: Some(card::Back {
+                        base: bellibolt.base,
+                        foil: bellibolt.foil,
+                        etching: None,
+                    }),
+                    ..umbreon()
+                }
+            },
+            rotation: (0.2, 2.8),
+            configuration: Configuration::default(),
+            software: SOFTWARE_LOOSE,
+        },
+    ]
+}
+
+fn compare(name: &str, pixels: &[u8], tolerance: Tolerance) -> Result<(), String> {
+    if tolerance.bless && std::env::var_os("HOLOFOIL_BLESS").is_some() {
+        write_png(&references().join(format!("{name}.png")), pixels);
+        return Ok(());
+    }
+
+    let expected = reference(name)?;
+
+    let differences: Vec<u8> = pixels
+        .chunks_exact(4)
+        .zip(expected.chunks_exact(4))
+        .map(|(a, b)| a.iter().zip(b).map(|(a, b)| a.abs_diff(*b)).max().unwrap())
+        .collect();
+
+    let different = differences
+        .iter()
+        .filter(|difference| **difference > tolerance.threshold)
+        .count();
+
+    let ratio = different as f32 / (SIZE * SIZE) as f32;
+    let max = differences.iter().copied().max().unwrap_or(0);
+
+    if ratio > tolerance.max_different || max > tolerance.max_difference {
+        let actual = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+            .join(tolerance.name)
+            .join(format!("{name}.png"));
+
+        write_png(&actual, pixels);
+
+        return Err(format!(
+            "{name}: {:.2}% of the pixels differ from the reference, by up to {max}; see {}",
+            ratio * 100.0,
+            actual.display()
+        ));
+    }
+
+    Ok(())
+}
+
+fn reference(name: &str) -> Result<Vec<u8>, String> {
+    let path = references().join(format!("{name}.png"));
+
+    let Ok(bytes) = fs::read(&path) else {
+        return Err(format!(
+            "{name}: missing {}; run with HOLOFOIL_BLESS=1 to create it",
+            path.display()
+        ));
+    };
+
+    let (expected, width, height) = decode(&bytes, false);
+
+    if (width, height) != (SIZE, SIZE) {
+        return Err(format!("{name}: the reference is {width}x{height}"));
+    }
+
+    Ok(expected)
+}
+
+fn references() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn viewport() -> card::Viewport {
+    card::Viewport {
+        x: 0,
+        y: 0,
+        width: SIZE,
+        height: SIZE,
+    }
+}
+
+fn rotation((x, y): (f32, f32)) -> Quaternion {
+    Quaternion::from_radians(Vector::X, x) * Quaternion::from_radians(Vector::Y, y)
+}
+
+// Clears the alpha of the base outside of an oval
+fn die_cut(structure: card::Structure) -> card::Structure {
+    let card::Image { width, height, .. } = structure.base;
+    let mut rgba = structure.base.rgba.to_vec();
+
+    for (i, pixel) in rgba.chunks_exact_mut(4).enumerate() {
+        let x = (i as u32 % width) as f32 / width as f32 * 2.0 - 1.0;
+        let y = (i as u32 / width) as f32 / height as f32 * 2.0 - 1.0;
+
+        if x * x + y * y > 1.0 {
+            pixel[3] = 0;
+        }
+    }
+
+    card::Structure {
+        base: card::Image {
+            rgba: Bytes::from(rgba),
+            ..structure.base
+        },
+        ..structure
+    }
+}
+
+fn label() -> card::Image {
+    let (width, height) = (256, 64);
+
+    let rgba = (0..width * height)
+        .flat_map(|i| {
+            let (x, y) = (i % width, i / width);
+            let stripe = if (x / 16 + y / 16) % 2 == 0 { 40 } else { 0 };
+
+            [200 - stripe, 30, (x * 255 / width) as u8, 255]
+        })
+        .collect::<Vec<u8>>();
+
+    card::Image {
+        rgba: Bytes::from(rgba),
+        width,
+        height,
+        compression: None,
+    }
+}
+
+fn scratches() -> card::Mask {
+    let (width, height) = (256, 358);
+
+    let pixels = (0..width * height)
+        .map(|i| {
+            let (x, y) = (i % width, i / width);
+
+            if (x + 2 * y) % 61 < 2 || (3 * x + 1024 - y) % 89 < 2 {
+                255
+            } else {
+                0
+            }
+        })
+        .collect::<Vec<u8>>();
+
+    card::Mask {
+        pixels: Bytes::from(pixels),
+        width,
+        height,
+        compression: None,
+    }
+}
+
+fn write_png(path: &PathBuf, pixels: &[u8]) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+    let mut encoder = png::Encoder::new(fs::File::create(path).unwrap(), SIZE, SIZE);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    encoder
+        .write_header()
+        .unwrap()
+        .write_image_data(pixels)
+        .unwrap();
+}
@@ -0,0 +1,117 @@
+//! Reloads a custom material from a file on a software adapter, like
+//! llvmpipe or lavapipe.
+//!
+//! The tests only run with the `software-tests` feature, and fail if no
+//! software adapter is available.
+#![cfg(feature = "hot-reload")]
+
+mod common;
+
+use holofoil::card;
+use holofoil::{Error, Pipeline, Quaternion, Settings};
+
+use std::borrow::Cow;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const SIZE: u32 = 64;
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn reloads_watched_material() {
+    let directory = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("hot_reload");
+    let path = directory.join("custom.wgsl");
+
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(&path, material("vec4(1.0, 0.0, 0.0, 1.0)")).unwrap();
+
+    let (device, queue) = common::device();
+
+    let mut pipeline = Pipeline::with_settings(
+        &device,
+        &queue,
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        common::blank(16, 22),
+        Settings {
+            anisotropy: 1,
+            material: Some(Cow::Owned(material("vec4(0.0, 0.0, 1.0, 1.0)"))),
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+
+    let mut card = pipeline
+        .upload(
+            &device,
+            &queue,
+            &card::Structure::new(common::blank(16, 22)),
+        )
+        .unwrap();
+
+    assert_eq!(center(&device, &queue, &pipeline, &mut card), [0, 0, 255]);
+
+    // The current contents of the file are applied first
+    pipeline.watch_material(&path).unwrap();
+
+    assert_eq!(reload(&mut pipeline, &device), Ok(true));
+    assert_eq!(center(&device, &queue, &pipeline, &mut card), [255, 0, 0]);
+
+    // Invalid changes keep the last good pipeline
+    fs::write(&path, material("vec3(0.0, 1.0, 0.0)")).unwrap();
+
+    assert!(matches!(
+        reload(&mut pipeline, &device),
+        Err(Error::InvalidShader(_))
+    ));
+    assert_eq!(center(&device, &queue, &pipeline, &mut card), [255, 0, 0]);
+
+    fs::write(&path, material("vec4(0.0, 1.0, 0.0, 1.0)")).unwrap();
+
+    assert_eq!(reload(&mut pipeline, &device), Ok(true));
+    assert_eq!(center(&device, &queue, &pipeline, &mut card), [0, 255, 0]);
+}
+
+fn material(color: &str) -> String {
+    format!("fn material(ctx: SurfaceContext) -> vec4<f32> {{ return {color}; }}\n")
+}
+
+// Reloads until a change of the file is noticed
+fn reload(pipeline: &mut Pipeline, device: &wgpu::Device) -> Result<bool, Error> {
+    let start = Instant::now();
+
+    loop {
+        match pipeline.reload(device) {
+            Ok(false) if start.elapsed() < Duration::from_secs(5) => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            result => return result,
+        }
+    }
+}
+
+fn center(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    pipeline: &Pipeline,
+    card: &mut holofoil::Card,
+) -> [u8; 3] {
+    let parameters = card::Parameters {
+        viewport: card::Viewport {
+            x: 0,
+            y: 0,
+            width: SIZE,
+            height: SIZE,
+        },
+        rotation: Quaternion::default(),
+    };
+
+    let pixels = common::render(device, queue, pipeline, card, parameters);
+    let center = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+
+    [pixels[center], pixels[center + 1], pixels[center + 2]]
+}
@@ -0,0 +1,86 @@
+//! Prepares cards through a [`wgpu::util::StagingBelt`] on a software
+//! adapter, like llvmpipe or lavapipe, and compares them with cards prepared
+//! right away.
+//!
+//! The tests only run with the `software-tests` feature, and fail if no
+//! software adapter is available.
+mod common;
+
+use holofoil::card;
+use holofoil::{CardCache, Quaternion, Vector};
+
+use std::sync::Arc;
+
+const SIZE: u32 = 128;
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn matches_prepare() {
+    let (device, queue) = common::device();
+    let pipeline = common::pipeline(&device, &queue, common::load_image("pokemon_tcg_back.png"));
+    let structure = Arc::new(common::umbreon());
+
+    let mut expected = pipeline.upload(&device, &queue, &structure).unwrap();
+    let expected = common::render(&device, &queue, &pipeline, &mut expected, parameters());
+
+    let mut card = pipeline.upload(&device, &queue, &structure).unwrap();
+
+    let mut cache = CardCache::new(u64::MAX);
+    cache.insert("umbreon", structure);
+    cache.begin_frame();
+
+    let mut belt = wgpu::util::StagingBelt::new(device.clone(), 1024);
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+
+    card.prepare_with(&mut encoder, &mut belt, parameters());
+
+    assert!(
+        cache
+            .prepare_with(
+                &pipeline,
+                &device,
+                &queue,
+                &mut encoder,
+                &mut belt,
+                &"umbreon",
+                parameters(),
+            )
+            .unwrap()
+    );
+
+    belt.finish();
+    let _ = queue.submit([encoder.finish()]);
+    belt.recall();
+
+    // Preparing the same parameters again writes nothing, so the renders
+    // show what the belt wrote
+    let rendered = common::render(&device, &queue, &pipeline, &mut card, parameters());
+    assert!(
+        rendered == expected,
+        "the card prepared with a belt differs"
+    );
+
+    let cached = common::draw(&device, &queue, SIZE, SIZE, |pass| {
+        cache.render(&pipeline, pass, &"umbreon");
+    });
+    assert!(
+        cached == expected,
+        "the cached card prepared with a belt differs"
+    );
+}
+
+fn parameters() -> card::Parameters {
+    card::Parameters {
+        viewport: card::Viewport {
+            x: 0,
+            y: 0,
+            width: SIZE,
+            height: SIZE,
+        },
+        rotation: Quaternion::from_radians(Vector::X, 0.3)
+            * Quaternion::from_radians(Vector::Y, 0.4),
+    }
+}
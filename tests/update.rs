@@ -0,0 +1,177 @@
+//! Updates the textures of cards uploaded to a software adapter, like
+//! llvmpipe or lavapipe.
+//!
+//! The tests only run with the `software-tests` feature, and fail if no
+//! software adapter is available.
+mod common;
+
+use holofoil::card;
+use holofoil::{Bytes, Error, Quaternion};
+
+const SIZE: u32 = 64;
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn missing_layers() {
+    let (device, queue) = common::device();
+    let pipeline = common::pipeline(&device, &queue, common::blank(16, 22));
+
+    let mut card = pipeline
+        .upload(
+            &device,
+            &queue,
+            &card::Structure::new(common::blank(16, 22)),
+        )
+        .unwrap();
+
+    let region = card::Region {
+        x: 0,
+        y: 0,
+        width: 1,
+        height: 1,
+    };
+
+    assert_eq!(
+        card.update_foil(&queue, region, &[255]),
+        Err(Error::MissingLayer {
+            layer: card::Layer::Foil
+        })
+    );
+
+    assert_eq!(
+        card.update_etching(&queue, region, &[255]),
+        Err(Error::MissingLayer {
+            layer: card::Layer::Etching
+        })
+    );
+
+    assert_eq!(card.update_base(&queue, region, &[0, 0, 0, 255]), Ok(()));
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn compressed_layers() {
+    let (device, queue) = common::device();
+    let pipeline = common::pipeline(&device, &queue, common::blank(16, 24));
+
+    // Every BC4 block of the mip chain, down to 1x1
+    let foil = card::Mask {
+        pixels: Bytes::from(vec![0; 34 * 8]),
+        width: 16,
+        height: 24,
+        compression: Some(card::Compression::Bc4),
+    };
+
+    let mut card = pipeline
+        .upload(
+            &device,
+            &queue,
+            &card::Structure {
+                foil: Some(foil),
+                ..card::Structure::new(common::blank(16, 24))
+            },
+        )
+        .unwrap();
+
+    let region = card::Region {
+        x: 0,
+        y: 0,
+        width: 1,
+        height: 1,
+    };
+
+    assert_eq!(
+        card.update_foil(&queue, region, &[255]),
+        Err(Error::Compressed {
+            layer: card::Layer::Foil
+        })
+    );
+}
+
+#[test]
+#[cfg_attr(
+    not(feature = "software-tests"),
+    ignore = "needs a software adapter; enable the `software-tests` feature"
+)]
+fn alpha_silhouette_follows_base() {
+    let (device, queue) = common::device();
+    let pipeline = common::pipeline(&device, &queue, common::blank(16, 22));
+
+    let mut card = pipeline
+        .upload(
+            &device,
+            &queue,
+            &card::Structure {
+                silhouette: Some(card::Silhouette::Alpha),
+                ..card::Structure::new(image(255))
+            },
+        )
+        .unwrap();
+
+    let center = card::Point {
+        x: SIZE as f32 / 2.0,
+        y: SIZE as f32 / 2.0,
+    };
+
+    assert!(card.hit_test(parameters(), center).is_some());
+    assert!(alpha(&render(&device, &queue, &pipeline, &mut card), center) > 0);
+
+    // Cutting out the whole base leaves nothing to hit or render
+    card.update_base(
+        &queue,
+        card::Region {
+            x: 0,
+            y: 0,
+            width: 16,
+            height: 22,
+        },
+        &image(0).rgba,
+    )
+    .unwrap();
+
+    assert!(card.hit_test(parameters(), center).is_none());
+    assert_eq!(
+        alpha(&render(&device, &queue, &pipeline, &mut card), center),
+        0
+    );
+}
+
+fn render(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    pipeline: &holofoil::Pipeline,
+    card: &mut holofoil::Card,
+) -> Vec<u8> {
+    common::render(device, queue, pipeline, card, parameters())
+}
+
+fn alpha(pixels: &[u8], point: card::Point) -> u8 {
+    pixels[(point.y as usize * SIZE as usize + point.x as usize) * 4 + 3]
+}
+
+fn parameters() -> card::Parameters {
+    card::Parameters {
+        viewport: card::Viewport {
+            x: 0,
+            y: 0,
+            width: SIZE,
+            height: SIZE,
+        },
+        rotation: Quaternion::default(),
+    }
+}
+
+fn image(alpha: u8) -> card::Image {
+    card::Image {
+        rgba: Bytes::from([128, 128, 128, alpha].repeat(16 * 22)),
+        width: 16,
+        height: 22,
+        compression: None,
+    }
+}